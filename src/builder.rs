@@ -0,0 +1,624 @@
+use std::{fmt, future::Future, sync::Arc, time::Duration};
+
+use reqwest::{
+    cookie::CookieStore,
+    header::{HeaderMap, HeaderValue},
+    Client as HttpClient, Url,
+};
+
+use crate::{
+    poll::Sleeper, BannedWordFilter, Client, Credentials, Error, IdempotencyStrategy, PollConfig,
+    BASE_URL, CARGO_PACKAGE_VERSION, FACE_ANIMATION_DIMENSIONS, FILE_STORAGE_BASE_URL,
+};
+
+const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const DEFAULT_POOL_IDLE_TIMEOUT: Option<Duration> = Some(Duration::from_secs(90));
+const DEFAULT_LOGIN_RETRIES: usize = 2;
+const DEFAULT_MAX_UPLOAD_SIZE: usize = 50 * 1024 * 1024;
+
+#[derive(Debug, Clone)]
+pub struct ClientBuilder {
+    base_url: String,
+    file_storage_base_url: String,
+    connect_timeout: Option<Duration>,
+    pool_idle_timeout: Option<Duration>,
+    pool_max_idle_per_host: Option<usize>,
+    redact_inference_text: bool,
+    redact_error_tokens: bool,
+    user_agent: Option<String>,
+    append_crate_user_agent: bool,
+    default_headers: HeaderMap,
+    cookie_store: Option<SharedCookieStore>,
+    max_inference_text_len: Option<usize>,
+    banned_word_filter: Option<BannedWordFilter>,
+    tts_credits_per_character: Option<f64>,
+    tts_poll_config: PollConfig,
+    face_animation_poll_config: PollConfig,
+    default_face_animation_dimensions: String,
+    voices_cache_ttl: Option<Duration>,
+    check_subscription_features: bool,
+    max_upload_size: usize,
+    sleeper: Option<Sleeper>,
+    dry_run: bool,
+    login_retries: usize,
+    idempotency_strategy: IdempotencyStrategy,
+}
+
+/// The resolved settings of a [`Client`], returned by [`Client::config`].
+#[derive(Debug, Clone)]
+pub struct ClientConfig {
+    pub(crate) base_url: String,
+    pub(crate) file_storage_base_url: String,
+    pub(crate) user_agent: String,
+    pub(crate) connect_timeout: Option<Duration>,
+    pub(crate) pool_idle_timeout: Option<Duration>,
+    pub(crate) pool_max_idle_per_host: Option<usize>,
+    #[cfg_attr(not(all(feature = "tracing", feature = "tts")), allow(dead_code))]
+    pub(crate) redact_inference_text: bool,
+    #[cfg_attr(not(any(feature = "tts", feature = "face_animator")), allow(dead_code))]
+    pub(crate) redact_error_tokens: bool,
+    #[cfg_attr(not(feature = "tts"), allow(dead_code))]
+    pub(crate) max_inference_text_len: Option<usize>,
+    #[cfg_attr(not(feature = "tts"), allow(dead_code))]
+    pub(crate) banned_word_filter: Option<BannedWordFilter>,
+    #[cfg_attr(not(feature = "tts"), allow(dead_code))]
+    pub(crate) tts_credits_per_character: Option<f64>,
+    #[cfg_attr(not(feature = "tts"), allow(dead_code))]
+    pub(crate) tts_poll_config: PollConfig,
+    #[cfg_attr(not(feature = "face_animator"), allow(dead_code))]
+    pub(crate) face_animation_poll_config: PollConfig,
+    pub(crate) default_face_animation_dimensions: String,
+    #[cfg_attr(not(feature = "voices"), allow(dead_code))]
+    pub(crate) voices_cache_ttl: Option<Duration>,
+    #[cfg_attr(not(feature = "face_animator"), allow(dead_code))]
+    pub(crate) check_subscription_features: bool,
+    #[cfg_attr(not(feature = "face_animator"), allow(dead_code))]
+    pub(crate) max_upload_size: usize,
+    pub(crate) sleeper: Option<Sleeper>,
+    pub(crate) dry_run: bool,
+    pub(crate) login_retries: usize,
+    #[cfg_attr(
+        not(any(
+            feature = "tts",
+            feature = "face_animator",
+            feature = "voice_conversion"
+        )),
+        allow(dead_code)
+    )]
+    pub(crate) idempotency_strategy: IdempotencyStrategy,
+}
+
+// reqwest needs a sized cookie store, this forwards to the caller's
+#[derive(Clone)]
+struct SharedCookieStore(Arc<dyn CookieStore>);
+
+impl CookieStore for SharedCookieStore {
+    fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, url: &Url) {
+        self.0.set_cookies(cookie_headers, url)
+    }
+
+    fn cookies(&self, url: &Url) -> Option<HeaderValue> {
+        self.0.cookies(url)
+    }
+}
+
+impl fmt::Debug for SharedCookieStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SharedCookieStore")
+    }
+}
+
+impl Default for ClientBuilder {
+    fn default() -> Self {
+        ClientBuilder {
+            base_url: BASE_URL.to_string(),
+            file_storage_base_url: FILE_STORAGE_BASE_URL.to_string(),
+            connect_timeout: Some(DEFAULT_CONNECT_TIMEOUT),
+            pool_idle_timeout: DEFAULT_POOL_IDLE_TIMEOUT,
+            pool_max_idle_per_host: None,
+            redact_inference_text: true,
+            redact_error_tokens: false,
+            user_agent: None,
+            append_crate_user_agent: true,
+            default_headers: HeaderMap::new(),
+            cookie_store: None,
+            max_inference_text_len: None,
+            banned_word_filter: None,
+            tts_credits_per_character: None,
+            tts_poll_config: PollConfig::default(),
+            face_animation_poll_config: PollConfig::new(Duration::from_secs(10)),
+            default_face_animation_dimensions: FACE_ANIMATION_DIMENSIONS[0].to_string(),
+            voices_cache_ttl: None,
+            check_subscription_features: false,
+            max_upload_size: DEFAULT_MAX_UPLOAD_SIZE,
+            #[cfg(feature = "tokio")]
+            sleeper: Some(Sleeper::tokio()),
+            #[cfg(not(feature = "tokio"))]
+            sleeper: None,
+            dry_run: false,
+            login_retries: DEFAULT_LOGIN_RETRIES,
+            idempotency_strategy: IdempotencyStrategy::Random,
+        }
+    }
+}
+
+impl ClientBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sends api requests to `base_url` instead of `https://api.fakeyou.com`, e.g. a proxy or a
+    /// mock server.
+    pub fn base_url<S: Into<String>>(mut self, base_url: S) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Resolves media paths against `file_storage_base_url` instead of the public FakeYou
+    /// bucket, it should end with the bucket name.
+    pub fn file_storage_base_url<S: Into<String>>(mut self, file_storage_base_url: S) -> Self {
+        self.file_storage_base_url = file_storage_base_url
+            .into()
+            .trim_end_matches('/')
+            .to_string();
+        self
+    }
+
+    /// Sets the connect timeout of the underlying http client, `None` disables it.
+    /// Defaults to 10 seconds.
+    pub fn connect_timeout(mut self, connect_timeout: Option<Duration>) -> Self {
+        self.connect_timeout = connect_timeout;
+        self
+    }
+
+    /// Sets how long idle pooled connections are kept alive, `None` keeps them indefinitely.
+    /// Defaults to 90 seconds.
+    pub fn pool_idle_timeout(mut self, pool_idle_timeout: Option<Duration>) -> Self {
+        self.pool_idle_timeout = pool_idle_timeout;
+        self
+    }
+
+    /// Caps the number of idle connections kept per host, unlimited by default.
+    pub fn pool_max_idle_per_host(mut self, pool_max_idle_per_host: usize) -> Self {
+        self.pool_max_idle_per_host = Some(pool_max_idle_per_host);
+        self
+    }
+
+    /// Controls whether `inference_text` is replaced with `[redacted]` in tracing spans.
+    /// Defaults to `true`, passwords are always redacted.
+    pub fn redact_inference_text(mut self, redact_inference_text: bool) -> Self {
+        self.redact_inference_text = redact_inference_text;
+        self
+    }
+
+    /// Shortens job tokens in errors to their last 4 characters, for logs shipped to third
+    /// parties. Disabled by default, a redacted token can't be used to resume a job.
+    pub fn redact_error_tokens(mut self, redact_error_tokens: bool) -> Self {
+        self.redact_error_tokens = redact_error_tokens;
+        self
+    }
+
+    /// Rejects inference text longer than `max_inference_text_len` characters before it is sent.
+    pub fn max_inference_text_len(mut self, max_inference_text_len: Option<usize>) -> Self {
+        self.max_inference_text_len = max_inference_text_len;
+        self
+    }
+
+    /// Rejects inference text containing a word of `banned_word_filter` with
+    /// `Error::BannedWord` before it is sent. No words are filtered by default.
+    pub fn banned_word_filter(mut self, banned_word_filter: BannedWordFilter) -> Self {
+        self.banned_word_filter = Some(banned_word_filter);
+        self
+    }
+
+    /// Sets the credits charged per character of inference text, used by
+    /// `Client::estimate_tts_cost`. Unset by default, so estimates carry no credits.
+    pub fn tts_credits_per_character(mut self, tts_credits_per_character: Option<f64>) -> Self {
+        self.tts_credits_per_character = tts_credits_per_character;
+        self
+    }
+
+    /// Controls how often tts jobs are polled, defaults to every 8 seconds.
+    pub fn tts_poll_config(mut self, tts_poll_config: PollConfig) -> Self {
+        self.tts_poll_config = tts_poll_config;
+        self
+    }
+
+    /// Controls how often face animation jobs are polled, defaults to every 10 seconds.
+    pub fn face_animation_poll_config(mut self, face_animation_poll_config: PollConfig) -> Self {
+        self.face_animation_poll_config = face_animation_poll_config;
+        self
+    }
+
+    /// Sets the `dimensions` preset of face animations that don't set one. Defaults to
+    /// `twitter_square`, which is hardcoded and may drift from the presets the server accepts,
+    /// see `Client::validate_default_face_animation_dimensions`.
+    pub fn default_face_animation_dimensions<S: Into<String>>(
+        mut self,
+        default_face_animation_dimensions: S,
+    ) -> Self {
+        self.default_face_animation_dimensions = default_face_animation_dimensions.into();
+        self
+    }
+
+    /// Serves `voices()` from memory for `voices_cache_ttl` after a fetch. Once it expires the
+    /// list is revalidated with its `ETag`, so an unchanged list isn't downloaded again.
+    pub fn voices_cache_ttl(mut self, voices_cache_ttl: Option<Duration>) -> Self {
+        self.voices_cache_ttl = voices_cache_ttl;
+        self
+    }
+
+    /// Checks the account's subscription before submitting requests that use premium only
+    /// features, such as `remove_watermark`, instead of silently getting a degraded result.
+    /// Disabled by default since it costs an extra request.
+    pub fn check_subscription_features(mut self, check_subscription_features: bool) -> Self {
+        self.check_subscription_features = check_subscription_features;
+        self
+    }
+
+    /// Rejects uploads larger than `max_upload_size` bytes before sending them.
+    /// Defaults to 50 MiB.
+    pub fn max_upload_size(mut self, max_upload_size: usize) -> Self {
+        self.max_upload_size = max_upload_size;
+        self
+    }
+
+    /// Sets the async sleep used between polls, e.g. `async_std::task::sleep`.
+    /// Defaults to `tokio::time::sleep` when the `tokio` feature is enabled.
+    pub fn sleep_fn<F, Fut>(mut self, sleep_fn: F) -> Self
+    where
+        F: Fn(Duration) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.sleeper = Some(Sleeper::new(sleep_fn));
+        self
+    }
+
+    /// Sleeps between polls with `std::thread::sleep`, the behaviour before polling became
+    /// async. This blocks the executor thread, so only use it when driving the client from a
+    /// blocking context with `block_on` and never inside a shared async runtime. A
+    /// `synthesize_deadline` deadline is then only noticed between polls, and needs the `tokio`
+    /// feature.
+    #[cfg(feature = "blocking")]
+    pub fn blocking_sleep(mut self) -> Self {
+        self.sleeper = Some(Sleeper::blocking());
+        self
+    }
+
+    /// Validates mutating calls (`tts_inference`, `voice_conversion`, uploads,
+    /// `create_facial_animation`, `create_tts_model`, `delete_tts_result`, `logout` and
+    /// keepalives) and returns a synthetic success instead of sending them, new jobs and
+    /// uploads get a `dry_run:` prefixed token.
+    /// Polling such a token completes right away without a request, so flows like `synthesize`
+    /// and `animate_face` run end to end, but the media of their results doesn't exist.
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Retries a login that failed with a server error or timeout up to `login_retries` times
+    /// with exponential backoff, rejected credentials are never retried. Defaults to 2.
+    pub fn login_retries(mut self, login_retries: usize) -> Self {
+        self.login_retries = login_retries;
+        self
+    }
+
+    /// Sets how idempotency tokens are generated when a request doesn't set one, random by
+    /// default.
+    pub fn idempotency_strategy(mut self, idempotency_strategy: IdempotencyStrategy) -> Self {
+        self.idempotency_strategy = idempotency_strategy;
+        self
+    }
+
+    /// Sets the user agent sent with every request, the crate identifier is appended to it
+    /// unless disabled with [`ClientBuilder::append_crate_user_agent`].
+    pub fn user_agent<S: Into<String>>(mut self, user_agent: S) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    pub fn append_crate_user_agent(mut self, append_crate_user_agent: bool) -> Self {
+        self.append_crate_user_agent = append_crate_user_agent;
+        self
+    }
+
+    /// Sends `default_headers` with every request. A `User-Agent` in them is ignored, use
+    /// [`ClientBuilder::user_agent`] to change it.
+    pub fn default_headers(mut self, default_headers: HeaderMap) -> Self {
+        self.default_headers = default_headers;
+        self
+    }
+
+    /// Keeps the session cookie in `cookie_store` instead of a jar private to the client, so it
+    /// can be shared with other clients or persisted across restarts. Defaults to an in-memory
+    /// jar.
+    pub fn cookie_store(mut self, cookie_store: Arc<dyn CookieStore>) -> Self {
+        self.cookie_store = Some(SharedCookieStore(cookie_store));
+        self
+    }
+
+    fn composed_user_agent(&self) -> String {
+        let crate_user_agent = format!("chatterverse-fakeyou-client@{CARGO_PACKAGE_VERSION}");
+        match &self.user_agent {
+            Some(user_agent) if self.append_crate_user_agent => {
+                format!("{user_agent} {crate_user_agent}")
+            }
+            Some(user_agent) => user_agent.clone(),
+            None => crate_user_agent,
+        }
+    }
+
+    pub fn build(self) -> Result<Client, Error> {
+        // the user agent is set after the default headers so it takes precedence
+        let user_agent = self.composed_user_agent();
+        let mut http_client = HttpClient::builder()
+            .default_headers(self.default_headers.clone())
+            .user_agent(user_agent.as_str())
+            .pool_idle_timeout(self.pool_idle_timeout);
+        http_client = match self.cookie_store {
+            Some(cookie_store) => http_client.cookie_provider(Arc::new(cookie_store)),
+            None => http_client.cookie_store(true),
+        };
+        if let Some(connect_timeout) = self.connect_timeout {
+            http_client = http_client.connect_timeout(connect_timeout);
+        }
+        if let Some(pool_max_idle_per_host) = self.pool_max_idle_per_host {
+            http_client = http_client.pool_max_idle_per_host(pool_max_idle_per_host);
+        }
+        Ok(Client {
+            http_client: http_client.build()?,
+            config: Arc::new(ClientConfig {
+                base_url: self.base_url,
+                file_storage_base_url: self.file_storage_base_url,
+                user_agent,
+                connect_timeout: self.connect_timeout,
+                pool_idle_timeout: self.pool_idle_timeout,
+                pool_max_idle_per_host: self.pool_max_idle_per_host,
+                redact_inference_text: self.redact_inference_text,
+                redact_error_tokens: self.redact_error_tokens,
+                max_inference_text_len: self.max_inference_text_len,
+                banned_word_filter: self.banned_word_filter,
+                tts_credits_per_character: self.tts_credits_per_character,
+                tts_poll_config: self.tts_poll_config,
+                face_animation_poll_config: self.face_animation_poll_config,
+                default_face_animation_dimensions: self.default_face_animation_dimensions,
+                voices_cache_ttl: self.voices_cache_ttl,
+                check_subscription_features: self.check_subscription_features,
+                max_upload_size: self.max_upload_size,
+                sleeper: self.sleeper,
+                dry_run: self.dry_run,
+                login_retries: self.login_retries,
+                idempotency_strategy: self.idempotency_strategy,
+            }),
+            last_response_headers: Arc::default(),
+            authenticated: Arc::default(),
+            active_polls: Arc::default(),
+            #[cfg(feature = "voices")]
+            voice_cache: Arc::default(),
+        })
+    }
+
+    pub async fn login<U: Into<String>, P: Into<String>>(
+        self,
+        username: U,
+        password: P,
+    ) -> Result<Client, Error> {
+        self.login_with(Credentials::new(username, password)).await
+    }
+
+    pub async fn login_with(self, credentials: Credentials) -> Result<Client, Error> {
+        let client = self.build()?;
+        client.authenticate(&credentials).await?;
+        Ok(client)
+    }
+}
+
+impl ClientConfig {
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn file_storage_base_url(&self) -> &str {
+        &self.file_storage_base_url
+    }
+
+    pub fn user_agent(&self) -> &str {
+        &self.user_agent
+    }
+
+    pub fn connect_timeout(&self) -> Option<Duration> {
+        self.connect_timeout
+    }
+
+    pub fn pool_idle_timeout(&self) -> Option<Duration> {
+        self.pool_idle_timeout
+    }
+
+    pub fn pool_max_idle_per_host(&self) -> Option<usize> {
+        self.pool_max_idle_per_host
+    }
+
+    pub fn tts_poll_interval(&self) -> Duration {
+        self.tts_poll_config.interval
+    }
+
+    pub fn face_animation_poll_interval(&self) -> Duration {
+        self.face_animation_poll_config.interval
+    }
+
+    pub fn default_face_animation_dimensions(&self) -> &str {
+        &self.default_face_animation_dimensions
+    }
+
+    pub fn max_inference_text_len(&self) -> Option<usize> {
+        self.max_inference_text_len
+    }
+
+    pub fn max_upload_size(&self) -> usize {
+        self.max_upload_size
+    }
+
+    pub fn voices_cache_ttl(&self) -> Option<Duration> {
+        self.voices_cache_ttl
+    }
+
+    pub fn login_retries(&self) -> usize {
+        self.login_retries
+    }
+
+    pub fn dry_run(&self) -> bool {
+        self.dry_run
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{MockResponse, MockServer};
+
+    #[tokio::test]
+    async fn connect_timeout_is_reflected_in_the_config() {
+        let server = MockServer::start();
+        server.mock(
+            "GET",
+            "/v1/session",
+            MockResponse::json(serde_json::json!({"logged_in": true})),
+        );
+        for connect_timeout in [Some(Duration::from_millis(100)), None] {
+            let client = server
+                .client_builder()
+                .connect_timeout(connect_timeout)
+                .build()
+                .unwrap();
+            assert_eq!(client.config().connect_timeout(), connect_timeout);
+            client.ping().await.unwrap();
+        }
+    }
+
+    #[test]
+    fn connect_timeout_defaults_to_ten_seconds() {
+        let client = Client::builder().build().unwrap();
+        assert_eq!(
+            client.config().connect_timeout(),
+            Some(Duration::from_secs(10))
+        );
+    }
+
+    #[test]
+    fn pool_settings_are_reflected_in_the_config() {
+        let client = Client::builder()
+            .pool_idle_timeout(Some(Duration::from_secs(30)))
+            .pool_max_idle_per_host(4)
+            .build()
+            .unwrap();
+        assert_eq!(
+            client.config().pool_idle_timeout(),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(client.config().pool_max_idle_per_host(), Some(4));
+    }
+
+    #[tokio::test]
+    async fn pool_max_idle_per_host_limits_connection_reuse() {
+        for (pool_max_idle_per_host, connections) in [(1, 1), (0, 2)] {
+            let server = MockServer::start();
+            server.mock(
+                "GET",
+                "/v1/session",
+                MockResponse::json(serde_json::json!({"logged_in": true})),
+            );
+            let client = server
+                .client_builder()
+                .pool_max_idle_per_host(pool_max_idle_per_host)
+                .build()
+                .unwrap();
+            client.ping().await.unwrap();
+            client.ping().await.unwrap();
+            assert_eq!(server.connections(), connections);
+        }
+    }
+
+    #[test]
+    fn config_reflects_the_builder_inputs() {
+        let client = Client::builder()
+            .base_url("http://localhost:1234")
+            .file_storage_base_url("http://localhost:1234/storage")
+            .connect_timeout(Some(Duration::from_secs(3)))
+            .user_agent("my-app/1.0")
+            .append_crate_user_agent(false)
+            .tts_poll_config(PollConfig::new(Duration::from_millis(250)))
+            .max_inference_text_len(Some(500))
+            .login_retries(2)
+            .dry_run(true)
+            .build()
+            .unwrap();
+        let config = client.config();
+        assert_eq!(config.base_url(), "http://localhost:1234");
+        assert_eq!(
+            config.file_storage_base_url(),
+            "http://localhost:1234/storage"
+        );
+        assert_eq!(config.connect_timeout(), Some(Duration::from_secs(3)));
+        assert_eq!(config.user_agent(), "my-app/1.0");
+        assert_eq!(config.tts_poll_interval(), Duration::from_millis(250));
+        assert_eq!(config.max_inference_text_len(), Some(500));
+        assert_eq!(config.login_retries(), 2);
+        assert!(config.dry_run());
+    }
+
+    #[tokio::test]
+    async fn injected_cookie_store_receives_the_session_cookie() {
+        let server = MockServer::start();
+        server
+            .mock(
+                "POST",
+                "/login",
+                MockResponse::json(serde_json::json!({"success": true}))
+                    .header("set-cookie", "session=abc123; Path=/; HttpOnly"),
+            )
+            .mock(
+                "GET",
+                "/v1/session",
+                MockResponse::json(serde_json::json!({"logged_in": true})),
+            );
+        let jar = Arc::new(reqwest::cookie::Jar::default());
+        server
+            .client_builder()
+            .cookie_store(jar.clone())
+            .login_with(Credentials::new("user", "password"))
+            .await
+            .unwrap();
+        let url = server.uri().parse::<Url>().unwrap();
+        let cookies = jar.cookies(&url).unwrap();
+        assert_eq!(cookies.to_str().unwrap(), "session=abc123");
+        // a second client sharing the jar is already logged in
+        let client = server.client_builder().cookie_store(jar).build().unwrap();
+        client.ping().await.unwrap();
+        let session = &server.received("GET", "/v1/session")[0];
+        assert_eq!(session.header("cookie"), Some("session=abc123"));
+    }
+
+    #[tokio::test]
+    async fn empty_credentials_are_rejected_before_logging_in() {
+        let server = MockServer::start();
+        server.mock(
+            "POST",
+            "/login",
+            MockResponse::json(serde_json::json!({"success": true})),
+        );
+        let password = String::from("password");
+        for credentials in [
+            Credentials::new("", password.clone()),
+            Credentials::new("user", "  "),
+            Credentials::new(" \t", password),
+        ] {
+            let error = server
+                .client_builder()
+                .login_with(credentials)
+                .await
+                .unwrap_err();
+            assert!(matches!(error, Error::EmptyCredentials), "{error:?}");
+        }
+        assert!(server.received("POST", "/login").is_empty());
+    }
+}
@@ -0,0 +1,37 @@
+/// The outcome of [`Client::api_compatibility_check`](crate::Client::api_compatibility_check),
+/// one entry per sampled endpoint.
+#[derive(Debug, Clone, Default)]
+pub struct CompatReport {
+    pub endpoints: Vec<EndpointCompatibility>,
+}
+
+impl CompatReport {
+    /// Whether every sampled endpoint was fetched and still deserializes into this crate's types.
+    pub fn is_compatible(&self) -> bool {
+        self.endpoints
+            .iter()
+            .all(|endpoint| endpoint.is_compatible())
+    }
+
+    /// The endpoints whose requests failed or whose responses no longer deserialize.
+    pub fn incompatible(&self) -> impl Iterator<Item = &EndpointCompatibility> {
+        self.endpoints
+            .iter()
+            .filter(|endpoint| !endpoint.is_compatible())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EndpointCompatibility {
+    pub endpoint: &'static str,
+    /// The deserialization error, if the sample no longer matches the typed response.
+    pub maybe_error: Option<String>,
+    /// The request error, if the sample couldn't be fetched. Nothing was deserialized then.
+    pub maybe_request_error: Option<String>,
+}
+
+impl EndpointCompatibility {
+    pub fn is_compatible(&self) -> bool {
+        self.maybe_error.is_none() && self.maybe_request_error.is_none()
+    }
+}
@@ -0,0 +1,64 @@
+use std::fmt;
+
+#[cfg(feature = "secrecy")]
+use secrecy::{ExposeSecret, SecretString};
+
+#[derive(Clone)]
+pub struct Credentials {
+    pub username_or_email: String,
+    #[cfg(feature = "secrecy")]
+    password: SecretString,
+    #[cfg(not(feature = "secrecy"))]
+    password: String,
+}
+
+impl Credentials {
+    pub fn new<U: Into<String>, P: Into<String>>(username_or_email: U, password: P) -> Self {
+        Credentials {
+            username_or_email: username_or_email.into(),
+            #[cfg(feature = "secrecy")]
+            password: SecretString::new(password.into()),
+            #[cfg(not(feature = "secrecy"))]
+            password: password.into(),
+        }
+    }
+
+    /// Whether either field is empty or only whitespace, the api would reject such a login.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.username_or_email.trim().is_empty() || self.password().trim().is_empty()
+    }
+
+    #[cfg(feature = "secrecy")]
+    pub(crate) fn password(&self) -> &str {
+        self.password.expose_secret()
+    }
+
+    #[cfg(not(feature = "secrecy"))]
+    pub(crate) fn password(&self) -> &str {
+        &self.password
+    }
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("username_or_email", &self.username_or_email)
+            .field("password", &"[redacted]")
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debug_redacts_the_password() {
+        let credentials = Credentials::new("user@example.com", "hunter2");
+        let debug = format!("{credentials:?}");
+        assert!(debug.contains("user@example.com"));
+        assert!(debug.contains("[redacted]"));
+        assert!(!debug.contains("hunter2"));
+        assert_eq!(credentials.password(), "hunter2");
+    }
+}
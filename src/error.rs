@@ -1,23 +1,149 @@
 use reqwest::StatusCode;
 
-#[cfg(feature = "face_animator")]
-use crate::FaceAnimationJobResponse;
+#[cfg(any(feature = "face_animator", feature = "voice_conversion"))]
+use crate::JobStatus;
 
 #[derive(thiserror::Error)]
 pub enum Error {
     #[error("Failed to authenticate user, check your credentials")]
     AuthenticationError,
+    #[error("Username or email and password must not be empty")]
+    EmptyCredentials,
     #[error("Too many requests")]
     TooManyRequestsError,
-    #[error("Tts job '{0}' was unsuccessful")]
-    TtsJobFailed(String),
+    #[error("Requested resource was not found")]
+    NotFound,
+    #[error("Inference text is empty")]
+    EmptyInput,
+    #[cfg(feature = "tts")]
+    #[error("No job tokens to poll")]
+    NoJobTokens,
+    #[error("Inference text is {len} characters long, the maximum is {max}")]
+    InputTooLong { len: usize, max: usize },
+    #[error("Tts option {option} is {value}, it must be between {min} and {max}")]
+    TtsOptionOutOfRange {
+        option: &'static str,
+        value: f32,
+        min: f32,
+        max: f32,
+    },
+    #[error("Inference text contains the banned word '{word}'")]
+    BannedWord { word: String },
+    #[cfg(feature = "language_tags")]
+    #[error("'{tag}' is not a valid language tag")]
+    InvalidLanguageTag { tag: String },
+    #[error("Upload is {size} bytes, the maximum is {max}")]
+    UploadTooLarge { size: usize, max: usize },
+    #[error("Audio has a sample rate of {actual}Hz, the model expects {expected}Hz")]
+    SampleRateMismatch { expected: u32, actual: u32 },
+    #[error("Tts job '{job_token}' was unsuccessful (retryable: {retryable})")]
+    TtsJobFailed {
+        job_token: String,
+        retryable: bool,
+        maybe_assigned_worker: Option<String>,
+        maybe_assigned_cluster: Option<String>,
+    },
     #[cfg(feature = "face_animator")]
-    #[error("Face animation job was unsuccessful: {0:?}")]
-    FaceAnimationJobFailed(FaceAnimationJobResponse),
+    #[error("Face animation job '{job_token}' was unsuccessful with status {status:?}")]
+    FaceAnimationJobFailed {
+        job_token: String,
+        status: JobStatus,
+        maybe_failure_category: Option<String>,
+        maybe_extra_status_description: Option<String>,
+    },
+    #[cfg(feature = "face_animator")]
+    #[error("Face animation dimensions '{dimensions}' aren't accepted by the server")]
+    UnknownFaceAnimationDimensions { dimensions: String },
+    #[cfg(feature = "voice_conversion")]
+    #[error("Voice conversion job '{job_token}' was unsuccessful with status {status:?}")]
+    VoiceConversionJobFailed {
+        job_token: String,
+        status: JobStatus,
+    },
+    #[error("Failed to download '{url}', server responded with {status}")]
+    DownloadFailed { url: String, status: StatusCode },
+    #[error("Request failed with status {status}")]
+    Http {
+        status: StatusCode,
+        #[source]
+        source: reqwest::Error,
+    },
+    #[error("Api request was unsuccessful: {message}")]
+    ApiError {
+        error_type: Option<String>,
+        message: String,
+    },
+    #[error("'{feature}' requires a FakeYou subscription")]
+    FeatureRequiresSubscription { feature: &'static str },
+    #[error("Job '{job_token}' didn't finish after {polls} polls")]
+    PollLimitExceeded { job_token: String, polls: usize },
+    #[error("Polling job '{job_token}' was cancelled")]
+    PollCancelled { job_token: String },
+    #[error("Deadline exceeded (job token: {job_token:?})")]
+    Deadline { job_token: Option<String> },
+    #[error("{source} (correlation id: {correlation_id})")]
+    Correlated {
+        correlation_id: String,
+        #[source]
+        source: Box<Error>,
+    },
     #[error(transparent)]
     InternalError(#[from] anyhow::Error),
 }
 
+impl Error {
+    /// Server errors and timeouts are worth retrying, auth and client errors are not.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Error::Correlated { source, .. } => source.is_transient(),
+            Error::Http { status, .. } => status.is_server_error(),
+            Error::InternalError(e) => e
+                .downcast_ref::<reqwest::Error>()
+                .is_some_and(|e| e.is_timeout() || e.is_connect()),
+            _ => false,
+        }
+    }
+
+    /// The correlation id of the `RequestOptions` of the failed request, if one was set.
+    pub fn correlation_id(&self) -> Option<&str> {
+        match self {
+            Error::Correlated { correlation_id, .. } => Some(correlation_id),
+            _ => None,
+        }
+    }
+
+    /// A summary of the error to paste into a support ticket, failed tts jobs include the
+    /// worker and cluster that ran them.
+    pub fn support_info(&self) -> String {
+        match self {
+            Error::TtsJobFailed {
+                job_token,
+                maybe_assigned_worker,
+                maybe_assigned_cluster,
+                ..
+            } => format!(
+                "{self}\njob token: {job_token}\nworker: {}\ncluster: {}",
+                maybe_assigned_worker.as_deref().unwrap_or("unknown"),
+                maybe_assigned_cluster.as_deref().unwrap_or("unknown"),
+            ),
+            _ => self.to_string(),
+        }
+    }
+
+    pub fn status_code(&self) -> Option<u16> {
+        match self {
+            Error::Correlated { source, .. } => source.status_code(),
+            Error::AuthenticationError => Some(StatusCode::UNAUTHORIZED.as_u16()),
+            Error::TooManyRequestsError => Some(StatusCode::TOO_MANY_REQUESTS.as_u16()),
+            Error::NotFound => Some(StatusCode::NOT_FOUND.as_u16()),
+            Error::DownloadFailed { status, .. } | Error::Http { status, .. } => {
+                Some(status.as_u16())
+            }
+            _ => None,
+        }
+    }
+}
+
 impl From<reqwest::Error> for Error {
     #[allow(clippy::needless_return)]
     fn from(e: reqwest::Error) -> Self {
@@ -29,8 +155,11 @@ impl From<reqwest::Error> for Error {
                 StatusCode::TOO_MANY_REQUESTS => {
                     return Error::TooManyRequestsError;
                 }
+                StatusCode::NOT_FOUND => {
+                    return Error::NotFound;
+                }
                 _ => {
-                    return Error::InternalError(e.into());
+                    return Error::Http { status, source: e };
                 }
             };
         } else {
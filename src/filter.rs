@@ -0,0 +1,31 @@
+use std::collections::HashSet;
+
+/// A caller supplied list of words that are rejected before inference text is submitted,
+/// to avoid wasting a request on text the api would reject. Matches whole words, ignoring case.
+#[derive(Debug, Clone, Default)]
+pub struct BannedWordFilter {
+    words: HashSet<String>,
+}
+
+impl BannedWordFilter {
+    pub fn new<I, S>(words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        BannedWordFilter {
+            words: words
+                .into_iter()
+                .map(|word| word.as_ref().to_lowercase())
+                .collect(),
+        }
+    }
+
+    /// Returns the first banned word found in `text`.
+    pub fn find(&self, text: &str) -> Option<&str> {
+        text.split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .find_map(|word| self.words.get(&word.to_lowercase()))
+            .map(String::as_str)
+    }
+}
@@ -1,46 +1,318 @@
+pub mod builder;
+pub mod compat;
+pub mod credentials;
 pub mod error;
+pub mod filter;
+#[cfg(test)]
+mod mock;
+pub mod options;
+pub mod poll;
+pub mod retry;
 
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+};
 use std::time::Duration;
+#[cfg(feature = "tokio")]
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
 
-#[cfg(feature = "face_animator")]
+pub use builder::{ClientBuilder, ClientConfig};
+use bytes::Bytes;
+pub use compat::{CompatReport, EndpointCompatibility};
+pub use credentials::Credentials;
+#[cfg(any(feature = "face_animator", feature = "model_management"))]
 use derive_builder::Builder;
 pub use error::Error;
-use reqwest::Client as HttpClient;
-use serde::{Deserialize, Serialize};
+pub use filter::BannedWordFilter;
+#[cfg(feature = "tts")]
+use futures::future::Either;
+#[cfg(any(feature = "tts", feature = "tokio", feature = "voice_conversion"))]
+use futures::StreamExt;
+use futures::{
+    future::{AbortHandle, Abortable},
+    Stream, TryStreamExt,
+};
+#[cfg(feature = "face_animator")]
+use futures::{AsyncRead, AsyncReadExt};
+pub use options::{IdempotencyInput, IdempotencyStrategy, RequestOptions, TtsInferenceOptions};
+pub use poll::PollConfig;
+// timings follow tokio's clock where there is one, so they respect `tokio::time::pause`
+#[cfg(any(
+    feature = "tts",
+    feature = "face_animator",
+    feature = "voice_conversion"
+))]
+use poll::PollSchedule;
+#[cfg(feature = "tokio")]
+use reqwest::header::{CONTENT_RANGE, RANGE};
+#[cfg(feature = "voices")]
+use reqwest::header::{ETAG, IF_NONE_MATCH};
+use reqwest::{header::HeaderMap, Client as HttpClient, Response, StatusCode};
+#[cfg(feature = "face_animator")]
+use reqwest::{
+    multipart::{Form, Part},
+    Body,
+};
+pub use retry::RetryPolicy;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::json;
+#[cfg(not(feature = "tokio"))]
+use std::time::Instant;
+#[cfg(feature = "tokio")]
+use tokio::io::AsyncWriteExt;
+#[cfg(feature = "tokio")]
+use tokio::time::Instant;
+#[cfg(feature = "tracing")]
+use tracing::Instrument;
 use uuid::Uuid;
 
 const BASE_URL: &str = "https://api.fakeyou.com";
 const FILE_STORAGE_BASE_URL: &str = "https://storage.googleapis.com/vocodes-public";
+const FILE_STORAGE_BUCKET: &str = "vocodes-public";
 const CARGO_PACKAGE_VERSION: &str = env!("CARGO_PKG_VERSION");
+const MAX_JOB_ATTEMPTS: u32 = 3;
+// resets below this (2001-09-09) are relative seconds rather than unix timestamps
+const RATE_LIMIT_RESET_EPOCH_THRESHOLD: u64 = 1_000_000_000;
+// below this share of the limit the remaining requests are spread out until the reset
+const RATE_LIMIT_LOW_FRACTION: f64 = 0.1;
+#[cfg(feature = "tts")]
+const WARMUP_INFERENCE_TEXT: &str = "Hi.";
+#[cfg(feature = "tts")]
+const SPOKEN_CHARACTERS_PER_SECOND: f64 = 15.0;
+const REDACTED_TOKEN_VISIBLE_CHARS: usize = 4;
+#[cfg(any(
+    feature = "tts",
+    feature = "face_animator",
+    feature = "model_management",
+    feature = "voice_conversion"
+))]
+const DRY_RUN_TOKEN_PREFIX: &str = "dry_run:";
+#[cfg(feature = "tokio")]
+const DOWNLOAD_CONCURRENCY: usize = 4;
+const STORAGE_RETRIES: usize = 3;
+const STORAGE_RETRY_BACKOFF: Duration = Duration::from_millis(500);
+const LOGIN_RETRY_BACKOFF: Duration = Duration::from_millis(500);
+#[cfg(feature = "face_animator")]
+const KEEPALIVE_RETRIES: usize = 2;
+#[cfg(feature = "face_animator")]
+const KEEPALIVE_RETRY_BACKOFF: Duration = Duration::from_millis(500);
+#[cfg(feature = "face_animator")]
+const UPLOAD_STREAM_CHUNK_SIZE: usize = 64 * 1024;
+pub(crate) const FACE_ANIMATION_DIMENSIONS: &[&str] =
+    &["twitter_square", "twitter_landscape", "twitter_portrait"];
+#[cfg(feature = "tracing")]
+const REDACTED: &str = "[redacted]";
 
+pub type JobToken = String;
+pub type ModelToken = String;
+
+/// Cheap to clone, clones share the connection pool, session cookies and all cached state,
+/// so a single client can be handed to many tasks. Keep any new state behind an `Arc`.
 #[derive(Debug, Clone)]
 pub struct Client {
     http_client: HttpClient,
+    config: Arc<ClientConfig>,
+    last_response_headers: Arc<Mutex<Option<ResponseHeaders>>>,
+    authenticated: Arc<AtomicBool>,
+    active_polls: Arc<Mutex<ActivePolls>>,
+    #[cfg(feature = "voices")]
+    voice_cache: Arc<Mutex<Option<VoiceCache>>>,
 }
 
 impl Client {
-    pub async fn from_login_credentials<S: Into<String>>(
-        username: S,
-        password: S,
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::new()
+    }
+
+    pub fn config(&self) -> &ClientConfig {
+        &self.config
+    }
+
+    pub async fn from_login_credentials<U: Into<String>, P: Into<String>>(
+        username: U,
+        password: P,
     ) -> Result<Self, Error> {
-        let http_client = HttpClient::builder()
-            .connect_timeout(std::time::Duration::from_secs(10))
-            .user_agent(format!(
-                "chatterverse-fakeyou-client@{CARGO_PACKAGE_VERSION}"
-            ))
-            .cookie_store(true)
-            .build()?;
-        http_client
-            .post(format!("{BASE_URL}/login"))
-            .json(&json!({
-                "username_or_email": username.into(),
-                "password": password.into()
-            }))
+        Client::login(Credentials::new(username, password)).await
+    }
+
+    pub async fn login(credentials: Credentials) -> Result<Self, Error> {
+        Client::builder().login_with(credentials).await
+    }
+
+    pub(crate) async fn authenticate(&self, credentials: &Credentials) -> Result<(), Error> {
+        if credentials.is_empty() {
+            return Err(Error::EmptyCredentials);
+        }
+        #[cfg(feature = "tracing")]
+        let span = tracing::debug_span!(
+            "login",
+            username_or_email = %credentials.username_or_email,
+            password = REDACTED
+        );
+        let policy = RetryPolicy::new(self.config.login_retries + 1).backoff(LOGIN_RETRY_BACKOFF);
+        let request = self.retry(&policy, || async {
+            self.http_client
+                .post(format!("{}/login", self.config.base_url))
+                .json(&json!({
+                    "username_or_email": credentials.username_or_email,
+                    "password": credentials.password()
+                }))
+                .send()
+                .await?
+                .error_for_api_status(&self.authenticated)
+                .await?;
+            Ok(())
+        });
+        #[cfg(feature = "tracing")]
+        let request = request.instrument(span);
+        let result = request.await;
+        self.track_authentication(&result);
+        result
+    }
+
+    /// Whether this client is logged in, without making a request. Set by a successful login or
+    /// `ping` and cleared by `logout` or by any request the api rejects with a 401, so a session
+    /// that expired on the server is noticed on the next request that needs it.
+    pub fn is_authenticated(&self) -> bool {
+        self.authenticated.load(Ordering::Relaxed)
+    }
+
+    pub async fn logout(&self) -> Result<(), Error> {
+        if self.config.dry_run {
+            return Ok(());
+        }
+        self.http_client
+            .post(format!("{}/logout", self.config.base_url))
+            .send()
+            .await?
+            .error_for_api_status(&self.authenticated)
+            .await?;
+        self.authenticated.store(false, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn track_authentication<T>(&self, result: &Result<T, Error>) {
+        match result {
+            Ok(_) => self.authenticated.store(true, Ordering::Relaxed),
+            Err(Error::AuthenticationError) => self.authenticated.store(false, Ordering::Relaxed),
+            Err(_) => {}
+        }
+    }
+
+    pub async fn ping(&self) -> Result<(), Error> {
+        let result = self.session().await.and_then(|session| {
+            if session.logged_in {
+                Ok(())
+            } else {
+                Err(Error::AuthenticationError)
+            }
+        });
+        self.track_authentication(&result);
+        result
+    }
+
+    async fn session(&self) -> Result<SessionResponse, Error> {
+        let session = self
+            .http_client
+            .get(format!("{}/v1/session", self.config.base_url))
+            .send()
+            .await?
+            .error_for_api_status(&self.authenticated)
+            .await?
+            .json::<SessionResponse>()
+            .await?;
+        Ok(session)
+    }
+
+    /// Fetches a sample from each endpoint this crate reads without a job or model token and
+    /// attempts the typed deserialize, to spot api changes before a real call fails. An endpoint
+    /// whose request fails is recorded as such and the remaining endpoints are still checked.
+    pub async fn api_compatibility_check(&self) -> Result<CompatReport, Error> {
+        #[cfg_attr(
+            not(any(
+                feature = "voices",
+                feature = "voice_conversion",
+                feature = "leaderboard"
+            )),
+            allow(unused_mut)
+        )]
+        let mut endpoints = vec![
+            self.check_endpoint::<SessionResponse>("/v1/session", None)
+                .await,
+        ];
+        #[cfg(feature = "voices")]
+        endpoints.push(
+            self.check_endpoint::<Vec<TtsVoice>>("/tts/list", Some("models"))
+                .await,
+        );
+        #[cfg(feature = "voice_conversion")]
+        endpoints.push(
+            self.check_endpoint::<Vec<VoiceConversionModel>>(
+                "/v1/voice_conversion/model_list",
+                Some("models"),
+            )
+            .await,
+        );
+        #[cfg(feature = "leaderboard")]
+        endpoints.push(
+            self.check_endpoint::<LeaderboardResponse>("/leaderboard", None)
+                .await,
+        );
+        Ok(CompatReport { endpoints })
+    }
+
+    async fn check_endpoint<T: DeserializeOwned>(
+        &self,
+        endpoint: &'static str,
+        maybe_property: Option<&str>,
+    ) -> EndpointCompatibility {
+        let response = match self.fetch_compat_sample(endpoint).await {
+            Ok(response) => response,
+            Err(e) => {
+                return EndpointCompatibility {
+                    endpoint,
+                    maybe_error: None,
+                    maybe_request_error: Some(e.to_string()),
+                }
+            }
+        };
+        let sample = match maybe_property {
+            Some(property) => response.get(property).cloned(),
+            None => Some(response),
+        };
+        let maybe_error = match sample {
+            Some(sample) => serde_json::from_value::<T>(sample)
+                .err()
+                .map(|e| e.to_string()),
+            None => maybe_property.map(|property| format!("missing '{property}' property")),
+        };
+        EndpointCompatibility {
+            endpoint,
+            maybe_error,
+            maybe_request_error: None,
+        }
+    }
+
+    async fn fetch_compat_sample(&self, endpoint: &str) -> Result<serde_json::Value, Error> {
+        let response = self
+            .http_client
+            .get(format!("{}{endpoint}", self.config.base_url))
             .send()
             .await?
-            .error_for_status()?;
-        Ok(Client { http_client })
+            .error_for_api_status(&self.authenticated)
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        match parse_error_envelope(&response) {
+            Some(e) => Err(e),
+            None => Ok(response),
+        }
     }
 
     pub async fn from_api_key<S: Into<String>>(_api_key: S) -> Result<Self, Error> {
@@ -53,324 +325,7073 @@ impl Client {
         tts_model_token: S,
         inference_text: S,
     ) -> Result<TtsInferenceResponse, Error> {
+        self.tts_inference_with(
+            tts_model_token,
+            inference_text,
+            TtsInferenceOptions::default(),
+            RequestOptions::default(),
+        )
+        .await
+    }
+
+    #[cfg(feature = "tts")]
+    pub async fn tts_inference_with<S: Into<String>>(
+        &self,
+        tts_model_token: S,
+        inference_text: S,
+        tts_options: TtsInferenceOptions,
+        options: RequestOptions,
+    ) -> Result<TtsInferenceResponse, Error> {
+        let tts_model_token = tts_model_token.into();
+        let inference_text = inference_text.into();
+        self.validate_inference_text(&inference_text)?;
+        tts_options.validate()?;
         let payload = TtsInferencePayload {
-            uuid_idempotency_token: Uuid::new_v4(),
-            tts_model_token: tts_model_token.into(),
-            inference_text: inference_text.into(),
+            uuid_idempotency_token: options.idempotency_token_or(
+                self.config.idempotency_strategy,
+                IdempotencyInput::TtsInference {
+                    tts_model_token: &tts_model_token,
+                    inference_text: &inference_text,
+                    tts_options: &tts_options,
+                },
+            ),
+            tts_model_token,
+            inference_text,
+            pitch: tts_options.pitch,
+            speed: tts_options.speed,
         };
-        let response = self
-            .http_client
-            .post(format!("{BASE_URL}/tts/inference"))
-            .json(&payload)
-            .send()
-            .await?
-            .error_for_status()?
-            .json::<TtsInferenceResponse>()
-            .await?;
-        Ok(response)
+        if self.config.dry_run {
+            return Ok(TtsInferenceResponse {
+                success: true,
+                error_type: None,
+                error_message: None,
+                error_reason: None,
+                inference_job_token: Some(dry_run_token()),
+                inference_job_token_type: None,
+                was_replay: false,
+            });
+        }
+        #[cfg(feature = "tracing")]
+        let span = tracing::debug_span!(
+            "tts_inference",
+            tts_model_token = %payload.tts_model_token,
+            correlation_id = options.correlation_id.as_deref(),
+            inference_text = if self.config.redact_inference_text {
+                REDACTED
+            } else {
+                payload.inference_text.as_str()
+            },
+        );
+        let request = async {
+            let response = options
+                .apply(
+                    self.http_client
+                        .post(format!("{}/tts/inference", self.config.base_url)),
+                )
+                .json(&payload)
+                .send()
+                .await?;
+            self.record_response_headers(response.headers());
+            let response = response
+                .error_for_api_status(&self.authenticated)
+                .await?
+                .json::<serde_json::Value>()
+                .await?;
+            // a replayed idempotency token is answered with the original job, which may be
+            // flagged as unsuccessful, so it must be checked before the error envelope
+            let response = match TtsInferenceResponse::deserialize(&response) {
+                Ok(response) if response.was_replay => response,
+                _ => deserialize_response::<TtsInferenceResponse>(response)?,
+            };
+            Ok(response)
+        };
+        #[cfg(feature = "tracing")]
+        let request = request.instrument(span);
+        options.correlate(request.await)
+    }
+
+    pub fn last_response_headers(&self) -> Option<ResponseHeaders> {
+        self.last_response_headers
+            .lock()
+            .expect("response headers lock poisoned")
+            .clone()
+    }
+
+    #[cfg_attr(not(feature = "tts"), allow(dead_code))]
+    fn record_response_headers(&self, headers: &HeaderMap) {
+        *self
+            .last_response_headers
+            .lock()
+            .expect("response headers lock poisoned") = Some(ResponseHeaders::from(headers));
+    }
+
+    #[cfg(feature = "tts")]
+    fn validate_inference_text(&self, inference_text: &str) -> Result<(), Error> {
+        if inference_text.trim().is_empty() {
+            return Err(Error::EmptyInput);
+        }
+        if let Some(word) = self
+            .config
+            .banned_word_filter
+            .as_ref()
+            .and_then(|filter| filter.find(inference_text))
+        {
+            return Err(Error::BannedWord {
+                word: word.to_string(),
+            });
+        }
+        let len = inference_text.chars().count();
+        match self.config.max_inference_text_len {
+            Some(max) if len > max => Err(Error::InputTooLong { len, max }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Estimates the cost of synthesizing `inference_text` without submitting it. The api has
+    /// no pricing endpoint, so credits come from `ClientBuilder::tts_credits_per_character`
+    /// and the duration from an average speaking rate. The model token is currently ignored.
+    #[cfg(feature = "tts")]
+    pub fn estimate_tts_cost(
+        &self,
+        _tts_model_token: &str,
+        inference_text: &str,
+    ) -> Result<CostEstimate, Error> {
+        self.validate_inference_text(inference_text)?;
+        let characters = inference_text.chars().count();
+        Ok(CostEstimate {
+            characters,
+            maybe_credits: self
+                .config
+                .tts_credits_per_character
+                .map(|rate| rate * characters as f64),
+            estimated_duration: Duration::from_secs_f64(
+                characters as f64 / SPOKEN_CHARACTERS_PER_SECOND,
+            ),
+        })
     }
 
+    /// Polls until the job reaches a terminal status. Dropping the future stops polling at
+    /// the next await point, including mid sleep, the job itself keeps running on the server.
     #[cfg(feature = "tts")]
     pub async fn poll_tts_job<S: Into<String> + Copy>(
         &self,
         inference_job_token: S,
     ) -> Result<TtsJobResponse, Error> {
-        loop {
-            let response = self
-                .http_client
-                .get(format!("{BASE_URL}/tts/job/{}", inference_job_token.into()))
-                .send()
-                .await?
-                .error_for_status()?
-                .json::<TtsJobResponse>()
-                .await?;
-            if !response.success {
-                break Err(Error::TtsJobFailed(response.state.job_token));
-            }
-            match response.state.status {
-                JobStatus::AttemptFailed | JobStatus::Pending | JobStatus::Started => {}
-                JobStatus::CompleteSuccess => {
+        let job_token = inference_job_token.into();
+        if is_dry_run_token(&job_token) {
+            return Ok(TtsJobResponse::dry_run(job_token));
+        }
+        self.cancellable_poll(job_token, async move {
+            let mut schedule = PollSchedule::new(&self.config.tts_poll_config);
+            loop {
+                let response = self
+                    .http_client
+                    .get(format!(
+                        "{}/tts/job/{}",
+                        self.config.base_url,
+                        inference_job_token.into()
+                    ))
+                    .send()
+                    .await?
+                    .error_for_api_status(&self.authenticated)
+                    .await?
+                    .json::<serde_json::Value>()
+                    .await?;
+                let response = deserialize_job_response::<TtsJobResponse>(response)?;
+                if !response.success {
+                    break Err(Error::TtsJobFailed {
+                        retryable: false,
+                        maybe_assigned_worker: response.state.maybe_assigned_worker,
+                        maybe_assigned_cluster: response.state.maybe_assigned_cluster,
+                        job_token: self.error_token(response.state.job_token),
+                    });
+                }
+                if response.state.status.is_failure() {
+                    break Err(Error::TtsJobFailed {
+                        retryable: response.state.is_retryable(),
+                        maybe_assigned_worker: response.state.maybe_assigned_worker,
+                        maybe_assigned_cluster: response.state.maybe_assigned_cluster,
+                        job_token: self.error_token(response.state.job_token),
+                    });
+                }
+                if response.state.status.is_terminal() {
                     break Ok(response);
                 }
-                JobStatus::CompleteFailure | JobStatus::Dead => {
-                    break Err(Error::TtsJobFailed(response.state.job_token));
+                if let Some(polls) = schedule.record_poll() {
+                    break Err(Error::PollLimitExceeded {
+                        job_token: self.error_token(response.state.job_token),
+                        polls,
+                    });
                 }
+                // sleep before making next request to prevent 429 errors, the server may ask
+                // for a longer interval when it is under load
+                let interval = response
+                    .state
+                    .maybe_recommended_poll_interval_ms
+                    .map(Duration::from_millis)
+                    .unwrap_or_else(|| schedule.next_interval());
+                self.sleep(interval).await?;
             }
-            // sleep before making next request to prevent 429 errors
-            std::thread::sleep(Duration::from_secs(8))
-        }
-    }
-
-    pub fn request_file_url(&self, public_bucket_media_path: &str) -> String {
-        format!("{FILE_STORAGE_BASE_URL}{public_bucket_media_path}")
+        })
+        .await
     }
 
-    #[cfg(feature = "voices")]
-    pub async fn voices(&self) -> Result<Vec<TtsVoice>, Error> {
+    /// Fetches the job status once and returns the json as is, including fields the typed
+    /// responses don't know about. Meant for troubleshooting.
+    #[cfg(feature = "tts")]
+    pub async fn tts_job_raw<S: Into<String>>(
+        &self,
+        inference_job_token: S,
+    ) -> Result<serde_json::Value, Error> {
         let response = self
             .http_client
-            .get(format!("{BASE_URL}/tts/list"))
+            .get(format!(
+                "{}/tts/job/{}",
+                self.config.base_url,
+                inference_job_token.into()
+            ))
             .send()
             .await?
-            .error_for_status()?
+            .error_for_api_status(&self.authenticated)
+            .await?
             .json::<serde_json::Value>()
             .await?;
-        let response = response.get("models").ok_or(anyhow::anyhow!(
-            "Invalid response body: missing 'models' property"
-        ))?;
-        let response = serde_json::from_value(response.to_owned())
-            .map_err(|_| anyhow::anyhow!("Failed to deserialize models"))?;
         Ok(response)
     }
 
-    #[cfg(feature = "face_animator")]
-    pub async fn upload_audio(&self, file: &[u8]) -> Result<UploadFileResponse, Error> {
-        let payload = UploadFilePayload {
-            uuid_idempotency_token: Uuid::new_v4(),
-            file,
-            source: "file",
-        };
+    #[cfg(feature = "tts")]
+    pub async fn resume_tts_job<S: Into<String> + Copy>(
+        &self,
+        inference_job_token: S,
+    ) -> Result<TtsResult, Error> {
+        self.poll_tts_job_to_result(inference_job_token).await
+    }
+
+    #[cfg(feature = "tts")]
+    pub async fn poll_tts_job_to_result<S: Into<String> + Copy>(
+        &self,
+        inference_job_token: S,
+    ) -> Result<TtsResult, Error> {
+        let response = self.poll_tts_job(inference_job_token).await?;
+        self.resolve_tts_result(response)
+    }
+
+    #[cfg(feature = "tts")]
+    pub async fn poll_many_tts_jobs(
+        &self,
+        inference_job_tokens: Vec<JobToken>,
+        concurrency: usize,
+    ) -> Vec<Result<TtsResult, Error>> {
+        futures::stream::iter(inference_job_tokens)
+            .map(|inference_job_token| async move {
+                self.poll_tts_job_to_result(inference_job_token.as_str())
+                    .await
+            })
+            .buffered(concurrency.max(1))
+            .collect()
+            .await
+    }
+
+    /// Polls every job at once and returns the first one to complete successfully, polling for
+    /// the others stops but their server side jobs keep running. Fails with the last error if
+    /// none of them succeed, and with `Error::NoJobTokens` if there are none to poll.
+    #[cfg(feature = "tts")]
+    pub async fn poll_first_complete(
+        &self,
+        inference_job_tokens: Vec<JobToken>,
+    ) -> Result<(JobToken, TtsResult), Error> {
+        if inference_job_tokens.is_empty() {
+            return Err(Error::NoJobTokens);
+        }
+        let mut polls = inference_job_tokens
+            .into_iter()
+            .map(|inference_job_token| {
+                Box::pin(async move {
+                    let result = self
+                        .poll_tts_job_to_result(inference_job_token.as_str())
+                        .await;
+                    (inference_job_token, result)
+                })
+            })
+            .collect::<Vec<_>>();
+        loop {
+            let ((inference_job_token, result), _, remaining) =
+                futures::future::select_all(polls).await;
+            match result {
+                Ok(result) => return Ok((inference_job_token, result)),
+                Err(e) if remaining.is_empty() => return Err(e),
+                Err(_) => polls = remaining,
+            }
+        }
+    }
+
+    #[cfg(feature = "tts")]
+    fn resolve_tts_result(&self, response: TtsJobResponse) -> Result<TtsResult, Error> {
+        let audio_path =
+            response
+                .state
+                .maybe_public_bucket_wav_audio_path
+                .ok_or(anyhow::anyhow!(
+                    "Invalid response body: missing 'maybe_public_bucket_wav_audio_path' property"
+                ))?;
+        Ok(TtsResult {
+            job_token: response.state.job_token,
+            audio_url: self.request_file_url(&audio_path),
+            maybe_spectrogram_url: response
+                .state
+                .maybe_public_bucket_spectrogram_path
+                .map(|path| self.request_file_url(&path)),
+            maybe_duration: response
+                .state
+                .maybe_duration_millis
+                .map(Duration::from_millis),
+            maybe_inference_text: None,
+        })
+    }
+
+    #[cfg(feature = "tts")]
+    pub async fn synthesize<S: Into<String>>(
+        &self,
+        tts_model_token: S,
+        inference_text: S,
+    ) -> Result<TtsResult, Error> {
+        let inference_text = inference_text.into();
         let response = self
-            .http_client
-            .post(format!("{BASE_URL}/media_uploads/upload_audio"))
-            .form(&payload)
-            .send()
-            .await?
-            .error_for_status()?
-            .json::<UploadFileResponse>()
+            .tts_inference(tts_model_token.into(), inference_text.clone())
             .await?;
-        Ok(response)
+        let job_token = inference_job_token(response)?;
+        let result = self.poll_tts_job_to_result(job_token.as_str()).await?;
+        Ok(TtsResult {
+            maybe_inference_text: Some(inference_text),
+            ..result
+        })
     }
 
-    #[cfg(feature = "face_animator")]
-    pub async fn upload_image(&self, file: &[u8]) -> Result<UploadFileResponse, Error> {
-        let payload = UploadFilePayload {
-            uuid_idempotency_token: Uuid::new_v4(),
-            file,
-            source: "file",
-        };
-        let response = self
-            .http_client
-            .post(format!("{BASE_URL}/media_uploads/upload_image"))
-            .form(&payload)
-            .send()
-            .await?
-            .error_for_status()?
-            .json::<UploadFileResponse>()
+    /// Loads a cold model ahead of a latency sensitive request. The api has no warmup
+    /// endpoint, so this synthesizes a short throwaway clip and waits for it to finish, which
+    /// counts as a regular inference.
+    #[cfg(feature = "tts")]
+    pub async fn warm_model<S: Into<String>>(&self, tts_model_token: S) -> Result<(), Error> {
+        self.synthesize(tts_model_token.into(), WARMUP_INFERENCE_TEXT.to_string())
             .await?;
-        Ok(response)
+        Ok(())
     }
 
-    #[cfg(feature = "face_animator")]
-    pub async fn create_facial_animation_builder(&self) -> CreateFaceAnimationPayloadBuilder {
-        CreateFaceAnimationPayloadBuilder::create_empty()
+    #[cfg(feature = "tts")]
+    pub async fn synthesize_and_download<S: Into<String>>(
+        &self,
+        tts_model_token: S,
+        inference_text: S,
+    ) -> Result<(TtsResult, Vec<u8>), Error> {
+        let result = self.synthesize(tts_model_token, inference_text).await?;
+        let audio = self.download_audio(&result.audio_url).await?;
+        Ok((result, audio))
     }
 
-    #[cfg(feature = "face_animator")]
-    pub async fn create_facial_animation(
+    #[cfg(feature = "tts")]
+    pub async fn synthesize_batch(
         &self,
-        payload: CreateFaceAnimationPayload,
-    ) -> Result<CreateFaceAnimationResponse, Error> {
-        let response = self
-            .http_client
-            .post(format!("{BASE_URL}/animation/face_animation/create"))
-            .json(&payload)
-            .send()
-            .await?
-            .error_for_status()?
-            .json::<CreateFaceAnimationResponse>()
-            .await?;
-        Ok(response)
+        tts_model_token: &str,
+        inference_texts: Vec<String>,
+        concurrency: usize,
+    ) -> Vec<Result<TtsResult, Error>> {
+        let submissions = futures::lock::Mutex::new(());
+        futures::stream::iter(inference_texts)
+            .map(|inference_text| {
+                let submissions = &submissions;
+                async move {
+                    let (result, _) = self
+                        .synthesize_paced(submissions, tts_model_token, inference_text)
+                        .await?;
+                    Ok(result)
+                }
+            })
+            .buffered(concurrency.max(1))
+            .collect()
+            .await
     }
 
-    #[cfg(feature = "face_animator")]
-    pub async fn poll_face_animation_job<T: Into<String> + Copy>(
+    /// Like `synthesize_batch`, with each result timed from its submission to the job's
+    /// terminal status, excluding any wait for the rate limit to reset.
+    #[cfg(feature = "tts")]
+    pub async fn synthesize_batch_timed(
         &self,
-        inference_token: T,
-    ) -> Result<FaceAnimationJobResponse, Error> {
-        loop {
+        tts_model_token: &str,
+        inference_texts: Vec<String>,
+        concurrency: usize,
+    ) -> Vec<Result<TimedResult, Error>> {
+        let submissions = futures::lock::Mutex::new(());
+        futures::stream::iter(inference_texts)
+            .map(|inference_text| {
+                let submissions = &submissions;
+                async move {
+                    let (result, submitted_at) = self
+                        .synthesize_paced(submissions, tts_model_token, inference_text)
+                        .await?;
+                    Ok(TimedResult {
+                        result,
+                        submit_to_complete: submitted_at.elapsed(),
+                    })
+                }
+            })
+            .buffered(concurrency.max(1))
+            .collect()
+            .await
+    }
+
+    // submissions take turns, so each one waits on the rate limit headers of the one before
+    // it instead of the whole first batch passing the check before any response arrived. Only
+    // the polling runs concurrently. Returns when the job was submitted, after any wait.
+    #[cfg(feature = "tts")]
+    async fn synthesize_paced(
+        &self,
+        submissions: &futures::lock::Mutex<()>,
+        tts_model_token: &str,
+        inference_text: String,
+    ) -> Result<(TtsResult, Instant), Error> {
+        let (response, submitted_at) = {
+            let _turn = submissions.lock().await;
+            self.wait_for_rate_limit().await?;
+            let submitted_at = Instant::now();
             let response = self
-                .http_client
-                .get(format!(
-                    "{BASE_URL}/model_inference/job_status/{}",
-                    inference_token.into()
-                ))
-                .send()
-                .await?
-                .error_for_status()?
-                .json::<FaceAnimationJobResponse>()
+                .tts_inference(tts_model_token.to_string(), inference_text.clone())
                 .await?;
-            if !response.success {
-                return Err(Error::FaceAnimationJobFailed(response));
-            }
-            match response.state.status.status {
-                JobStatus::AttemptFailed | JobStatus::Pending | JobStatus::Started => {}
-                JobStatus::CompleteSuccess => {
-                    return Ok(response);
-                }
-                JobStatus::CompleteFailure | JobStatus::Dead => {
-                    return Err(Error::FaceAnimationJobFailed(response));
-                }
+            (response, submitted_at)
+        };
+        let job_token = inference_job_token(response)?;
+        let result = self.poll_tts_job_to_result(job_token.as_str()).await?;
+        let result = TtsResult {
+            maybe_inference_text: Some(inference_text),
+            ..result
+        };
+        Ok((result, submitted_at))
+    }
+
+    // slows down as the last response's remaining requests run low, see `rate_limit_wait`
+    #[cfg(feature = "tts")]
+    async fn wait_for_rate_limit(&self) -> Result<(), Error> {
+        let wait = self
+            .last_response_headers()
+            .and_then(|headers| headers.rate_limit_wait());
+        match wait {
+            Some(wait) => self.sleep(wait).await,
+            None => Ok(()),
+        }
+    }
+
+    #[cfg(feature = "tts")]
+    pub async fn synthesize_batch_partition(
+        &self,
+        tts_model_token: &str,
+        inference_texts: Vec<String>,
+        concurrency: usize,
+    ) -> (Vec<TtsResult>, Vec<(usize, Error)>) {
+        let mut successes = Vec::new();
+        let mut failures = Vec::new();
+        let results = self
+            .synthesize_batch(tts_model_token, inference_texts, concurrency)
+            .await;
+        for (index, result) in results.into_iter().enumerate() {
+            match result {
+                Ok(result) => successes.push(result),
+                Err(e) => failures.push((index, e)),
             }
-            // sleep before making next request to prevent 429 errors
-            std::thread::sleep(Duration::from_secs(10))
         }
+        (successes, failures)
     }
-}
 
-#[derive(Clone, Debug, Serialize)]
-pub struct TtsInferencePayload {
-    uuid_idempotency_token: Uuid,
-    tts_model_token: String,
-    inference_text: String,
-}
+    #[cfg(feature = "tts")]
+    pub async fn synthesize_deadline<S: Into<String>>(
+        &self,
+        tts_model_token: S,
+        inference_text: S,
+        deadline: std::time::Instant,
+    ) -> Result<TtsResult, Error> {
+        let inference_text = inference_text.into();
+        let response = self
+            .before_deadline(
+                deadline,
+                self.tts_inference(tts_model_token.into(), inference_text.clone()),
+            )
+            .await?
+            .ok_or(Error::Deadline { job_token: None })??;
+        let job_token = inference_job_token(response)?;
+        let result = self
+            .before_deadline(deadline, self.poll_tts_job_to_result(job_token.as_str()))
+            .await?
+            .ok_or_else(|| Error::Deadline {
+                job_token: Some(self.error_token(job_token.clone())),
+            })??;
+        Ok(TtsResult {
+            maybe_inference_text: Some(inference_text),
+            ..result
+        })
+    }
 
-#[derive(Clone, Debug, Deserialize)]
-pub struct TtsInferenceResponse {
-    pub success: bool,
-    pub error_type: Option<String>,
-    pub error_message: Option<String>,
-    pub error_reason: Option<String>,
-    pub inference_job_token: Option<String>,
-    pub inference_job_token_type: Option<String>,
-}
+    // resolves to `None` if the deadline passes before the future completes. The deadline is
+    // timed by tokio rather than the configured sleep, which may block and would then hold up
+    // the future it races until the deadline
+    #[cfg(all(feature = "tts", feature = "tokio"))]
+    async fn before_deadline<F: Future>(
+        &self,
+        deadline: std::time::Instant,
+        future: F,
+    ) -> Result<Option<F::Output>, Error> {
+        let expired = tokio::time::sleep_until(deadline.into());
+        futures::pin_mut!(future, expired);
+        match futures::future::select(future, expired).await {
+            Either::Left((output, _)) => Ok(Some(output)),
+            Either::Right(_) => Ok(None),
+        }
+    }
 
-#[derive(Clone, Debug, Deserialize)]
-pub struct TtsJobResponse {
-    pub success: bool,
-    pub state: TtsJobState,
-}
+    // without tokio the configured sleep is the only timer, a blocking one can't be raced
+    #[cfg(all(feature = "tts", not(feature = "tokio")))]
+    async fn before_deadline<F: Future>(
+        &self,
+        deadline: std::time::Instant,
+        future: F,
+    ) -> Result<Option<F::Output>, Error> {
+        if self
+            .config
+            .sleeper
+            .as_ref()
+            .is_some_and(|sleeper| sleeper.is_blocking())
+        {
+            return Err(anyhow::anyhow!(
+                "Deadlines need the `tokio` feature when `blocking_sleep` is used"
+            )
+            .into());
+        }
+        let sleep = self.sleep(deadline.saturating_duration_since(std::time::Instant::now()));
+        futures::pin_mut!(future, sleep);
+        match futures::future::select(future, sleep).await {
+            Either::Left((output, _)) => Ok(Some(output)),
+            Either::Right((result, _)) => result.map(|_| None),
+        }
+    }
 
-#[derive(Clone, Debug, Deserialize)]
-pub struct TtsJobState {
+    /// Stops every poll of this client and its clones that is currently running, they fail
+    /// with `Error::PollCancelled`. The jobs themselves keep running on the server.
+    pub fn cancel_all_polls(&self) {
+        let mut active_polls = self
+            .active_polls
+            .lock()
+            .expect("active polls lock poisoned");
+        for (_, handle) in active_polls.handles.drain() {
+            handle.abort();
+        }
+    }
+
+    #[cfg_attr(
+        not(any(
+            feature = "tts",
+            feature = "face_animator",
+            feature = "voice_conversion"
+        )),
+        allow(dead_code)
+    )]
+    async fn cancellable_poll<T, F>(&self, job_token: String, poll: F) -> Result<T, Error>
+    where
+        F: Future<Output = Result<T, Error>>,
+    {
+        let (handle, registration) = AbortHandle::new_pair();
+        let _guard = ActivePollGuard::register(&self.active_polls, handle);
+        let result = Abortable::new(poll, registration).await;
+        result.unwrap_or_else(|_| {
+            Err(Error::PollCancelled {
+                job_token: self.error_token(job_token),
+            })
+        })
+    }
+
+    async fn sleep(&self, duration: Duration) -> Result<(), Error> {
+        let sleeper = self.config.sleeper.as_ref().ok_or(anyhow::anyhow!(
+            "No async sleep configured, enable the `tokio` feature or use `ClientBuilder::sleep_fn`"
+        ))?;
+        sleeper.sleep(duration).await;
+        Ok(())
+    }
+
+    #[cfg(feature = "tts")]
+    pub async fn delete_tts_result<S: Into<String>>(
+        &self,
+        tts_result_token: S,
+    ) -> Result<(), Error> {
+        if self.config.dry_run {
+            return Ok(());
+        }
+        self.http_client
+            .post(format!(
+                "{}/tts/result/{}/delete",
+                self.config.base_url,
+                tts_result_token.into()
+            ))
+            .json(&json!({
+                "set_delete": true,
+                "as_mod": false
+            }))
+            .send()
+            .await?
+            .error_for_api_status(&self.authenticated)
+            .await?;
+        Ok(())
+    }
+
+    #[cfg(feature = "tts")]
+    pub async fn tts_result<S: Into<String>>(
+        &self,
+        tts_result_token: S,
+    ) -> Result<TtsResultDetail, Error> {
+        let response = self
+            .http_client
+            .get(format!(
+                "{}/tts/result/{}",
+                self.config.base_url,
+                tts_result_token.into()
+            ))
+            .send()
+            .await?
+            .error_for_api_status(&self.authenticated)
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        let response = deserialize_response::<TtsResultDetailResponse>(response)?;
+        Ok(response.result)
+    }
+
+    #[cfg(feature = "tts")]
+    pub async fn recent_tts_results(&self, limit: usize) -> Result<Vec<TtsHistoryEntry>, Error> {
+        let response = self
+            .http_client
+            .get(format!("{}/tts/results", self.config.base_url))
+            .query(&[("limit", limit)])
+            .send()
+            .await?
+            .error_for_api_status(&self.authenticated)
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        let response = deserialize_response::<TtsHistoryResponse>(response)?;
+        Ok(response
+            .results
+            .into_iter()
+            .map(|entry| TtsHistoryEntry {
+                audio_url: self.request_file_url(&entry.public_bucket_wav_audio_path),
+                ..entry
+            })
+            .collect())
+    }
+
+    pub fn request_file_url(&self, public_bucket_media_path: &str) -> String {
+        let file_storage_base_url = &self.config.file_storage_base_url;
+        if public_bucket_media_path.starts_with(file_storage_base_url.as_str()) {
+            return public_bucket_media_path.to_string();
+        }
+        // some responses already include the bucket segment, avoid prefixing it twice
+        let path = public_bucket_media_path.trim_start_matches('/');
+        let path = path
+            .strip_prefix(FILE_STORAGE_BUCKET)
+            .filter(|rest| rest.starts_with('/'))
+            .unwrap_or(public_bucket_media_path)
+            .trim_start_matches('/');
+        format!("{file_storage_base_url}/{path}")
+    }
+
+    pub async fn download_audio(&self, public_bucket_media_path: &str) -> Result<Vec<u8>, Error> {
+        let response = self.fetch_media(public_bucket_media_path).await?;
+        Ok(response.bytes().await?.to_vec())
+    }
+
+    /// Like `download_audio`, but retries a 404 a few times with a short backoff. Storage is
+    /// eventually consistent, so a job that just completed may not be readable yet.
+    pub async fn download_audio_with_retry(
+        &self,
+        public_bucket_media_path: &str,
+    ) -> Result<Vec<u8>, Error> {
+        let policy = RetryPolicy::new(STORAGE_RETRIES + 1)
+            .backoff(STORAGE_RETRY_BACKOFF)
+            .retry_if(|e| {
+                matches!(e, Error::DownloadFailed { status, .. } if *status == StatusCode::NOT_FOUND)
+            });
+        self.retry(&policy, || self.download_audio(public_bucket_media_path))
+            .await
+    }
+
+    /// Runs `operation` until it succeeds, fails with an error `policy` doesn't retry or runs
+    /// out of attempts, sleeping with the client's sleep in between. Without a sleep configured
+    /// the first error is returned.
+    pub async fn retry<T, F, Fut>(&self, policy: &RetryPolicy, mut operation: F) -> Result<T, Error>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        let mut backoff = policy.backoff;
+        let mut attempts = 1;
+        loop {
+            match operation().await {
+                Err(e)
+                    if attempts < policy.max_attempts
+                        && (policy.retryable)(&e)
+                        && self.config.sleeper.is_some() =>
+                {
+                    attempts += 1;
+                    self.sleep(backoff).await?;
+                    backoff = backoff.mul_f64(policy.backoff_factor);
+                }
+                result => return result,
+            }
+        }
+    }
+
+    #[cfg(feature = "wav")]
+    pub async fn download_audio_samples(
+        &self,
+        public_bucket_media_path: &str,
+    ) -> Result<(Vec<f32>, u32), Error> {
+        let audio = self.download_audio(public_bucket_media_path).await?;
+        let reader = hound::WavReader::new(std::io::Cursor::new(audio))
+            .map_err(|e| anyhow::anyhow!("Failed to read wav audio: {e}"))?;
+        let spec = reader.spec();
+        let samples: Result<Vec<f32>, _> = match spec.sample_format {
+            hound::SampleFormat::Float => reader.into_samples::<f32>().collect(),
+            hound::SampleFormat::Int => {
+                let scale = (1_i64 << (spec.bits_per_sample - 1)) as f32;
+                reader
+                    .into_samples::<i32>()
+                    .map(|sample| sample.map(|sample| sample as f32 / scale))
+                    .collect()
+            }
+        };
+        let samples = samples.map_err(|e| anyhow::anyhow!("Failed to decode wav samples: {e}"))?;
+        Ok((samples, spec.sample_rate))
+    }
+
+    pub async fn audio_stream(
+        &self,
+        public_bucket_media_path: &str,
+    ) -> Result<impl Stream<Item = Result<Bytes, Error>>, Error> {
+        let response = self.fetch_media(public_bucket_media_path).await?;
+        Ok(response.bytes_stream().map_err(Error::from))
+    }
+
+    /// Downloads the results one after the other, reporting progress as they go. Every result
+    /// gets a `Started` event followed by `Progress` events and a final `Completed` with the audio,
+    /// or a `Failed` event at any point after which the next result starts.
+    pub fn download_results_stream<'a>(
+        &'a self,
+        results: &'a [TtsResult],
+    ) -> impl Stream<Item = DownloadEvent> + 'a {
+        let state = (
+            results.iter().enumerate(),
+            None::<(usize, Response, Vec<u8>)>,
+        );
+        futures::stream::unfold(state, move |(mut results, current)| async move {
+            if let Some((index, mut response, mut audio)) = current {
+                let event = match response.chunk().await {
+                    Ok(Some(chunk)) => {
+                        audio.extend_from_slice(&chunk);
+                        let event = DownloadEvent::Progress {
+                            index,
+                            downloaded: audio.len() as u64,
+                            maybe_total: response.content_length(),
+                        };
+                        return Some((event, (results, Some((index, response, audio)))));
+                    }
+                    Ok(None) => DownloadEvent::Completed { index, audio },
+                    Err(e) => DownloadEvent::Failed {
+                        index,
+                        error: e.into(),
+                    },
+                };
+                return Some((event, (results, None)));
+            }
+            let (index, result) = results.next()?;
+            match self.fetch_media(&result.audio_url).await {
+                Ok(response) => {
+                    let event = DownloadEvent::Started {
+                        index,
+                        maybe_total: response.content_length(),
+                    };
+                    Some((event, (results, Some((index, response, Vec::new())))))
+                }
+                Err(error) => Some((DownloadEvent::Failed { index, error }, (results, None))),
+            }
+        })
+    }
+
+    /// Downloads into `dest_path`, resuming from the size of an existing partial file with a
+    /// `Range` request. Servers that ignore the range restart the file from scratch, as does
+    /// a `Content-Range` that doesn't line up with the partial file, e.g. because the remote file
+    /// changed. Returns the size of the completed file.
+    #[cfg(feature = "tokio")]
+    pub async fn download_audio_resumable(
+        &self,
+        public_bucket_media_path: &str,
+        dest_path: impl AsRef<Path>,
+    ) -> Result<u64, Error> {
+        let dest_path = dest_path.as_ref();
+        let mut existing_len = match tokio::fs::metadata(dest_path).await {
+            Ok(metadata) => metadata.len(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => 0,
+            Err(e) => return Err(anyhow::Error::from(e).into()),
+        };
+        let url = self.request_file_url(public_bucket_media_path);
+        let (response, mut file) = loop {
+            let mut request = self.http_client.get(&url);
+            if existing_len > 0 {
+                request = request.header(RANGE, format!("bytes={existing_len}-"));
+            }
+            let response = request.send().await?;
+            let content_range = ContentRange::from_headers(response.headers());
+            let file = match response.status() {
+                StatusCode::PARTIAL_CONTENT
+                    if content_range.is_some_and(|range| range.start == Some(existing_len)) =>
+                {
+                    tokio::fs::OpenOptions::new()
+                        .append(true)
+                        .open(dest_path)
+                        .await
+                }
+                // the range starts at the end of the file, nothing left to download
+                StatusCode::RANGE_NOT_SATISFIABLE
+                    if existing_len > 0
+                        && content_range.is_some_and(|range| range.len == Some(existing_len)) =>
+                {
+                    return Ok(existing_len);
+                }
+                // the partial file doesn't match the remote one, start over without a range
+                StatusCode::PARTIAL_CONTENT | StatusCode::RANGE_NOT_SATISFIABLE
+                    if existing_len > 0 =>
+                {
+                    existing_len = 0;
+                    continue;
+                }
+                status if status.is_success() => tokio::fs::File::create(dest_path).await,
+                status => return Err(Error::DownloadFailed { url, status }),
+            }
+            .map_err(anyhow::Error::from)?;
+            break (response, file);
+        };
+        let mut stream = response.bytes_stream();
+        while let Some(chunk) = stream.try_next().await? {
+            file.write_all(&chunk).await.map_err(anyhow::Error::from)?;
+        }
+        file.flush().await.map_err(anyhow::Error::from)?;
+        let len = file.metadata().await.map_err(anyhow::Error::from)?.len();
+        Ok(len)
+    }
+
+    /// Downloads every result into `dir` under the name returned by `name_fn`, names that are
+    /// taken get a numeric suffix instead of overwriting. Returns the path or error of each
+    /// result in order, one failed download doesn't stop the others.
+    #[cfg(feature = "tokio")]
+    pub async fn download_results_to_dir(
+        &self,
+        results: &[TtsResult],
+        dir: impl AsRef<Path>,
+        name_fn: impl Fn(&TtsResult) -> String,
+    ) -> Result<Vec<Result<PathBuf, Error>>, Error> {
+        let dir = dir.as_ref();
+        tokio::fs::create_dir_all(dir)
+            .await
+            .map_err(anyhow::Error::from)?;
+        let mut taken = HashSet::new();
+        let mut paths = Vec::with_capacity(results.len());
+        for result in results {
+            paths.push(unique_path(dir, &name_fn(result), &mut taken).await?);
+        }
+        let downloads = futures::stream::iter(results.iter().zip(paths))
+            .map(|(result, path)| async move {
+                let audio = self.download_audio(&result.audio_url).await?;
+                tokio::fs::write(&path, audio)
+                    .await
+                    .map_err(anyhow::Error::from)?;
+                Ok(path)
+            })
+            .buffered(DOWNLOAD_CONCURRENCY)
+            .collect()
+            .await;
+        Ok(downloads)
+    }
+
+    async fn fetch_media(&self, public_bucket_media_path: &str) -> Result<Response, Error> {
+        let url = self.request_file_url(public_bucket_media_path);
+        let response = self.http_client.get(&url).send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(Error::DownloadFailed { url, status });
+        }
+        Ok(response)
+    }
+
+    #[cfg(feature = "voices")]
+    pub async fn voices(&self) -> Result<Vec<TtsVoice>, Error> {
+        Ok(self.voices_with_meta().await?.models)
+    }
+
+    #[cfg(feature = "voices")]
+    pub async fn voices_with_meta(&self) -> Result<VoicesResponse, Error> {
+        let (cached_response, etag) = {
+            let cache = self.voice_cache.lock().expect("voice cache lock poisoned");
+            match cache.as_ref() {
+                Some(cache) if cache.is_fresh(self.config.voices_cache_ttl) => {
+                    return Ok(cache.response.clone());
+                }
+                Some(cache) => (Some(cache.response.clone()), cache.etag.clone()),
+                None => (None, None),
+            }
+        };
+        let mut request = self
+            .http_client
+            .get(format!("{}/tts/list", self.config.base_url));
+        if let (Some(_), Some(etag)) = (&cached_response, &etag) {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        let response = request
+            .send()
+            .await?
+            .error_for_api_status(&self.authenticated)
+            .await?;
+        if response.status() == StatusCode::NOT_MODIFIED {
+            if let Some(cached_response) = cached_response {
+                self.cache_voices(cached_response.clone(), etag);
+                return Ok(cached_response);
+            }
+        }
+        let etag = response
+            .headers()
+            .get(ETAG)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let response = response.bytes().await?;
+        let response = serde_json::from_slice::<TtsListResponse>(&response)
+            .map_err(|_| anyhow::anyhow!("Failed to deserialize models"))?;
+        let response = VoicesResponse {
+            models: response.models.ok_or(anyhow::anyhow!(
+                "Invalid response body: missing 'models' property"
+            ))?,
+            maybe_pagination: response.maybe_pagination,
+            refresh_interval_millis: response.refresh_interval_millis,
+        };
+        self.cache_voices(response.clone(), etag);
+        Ok(response)
+    }
+
+    #[cfg(feature = "voices")]
+    pub async fn voices_filtered(&self, filter: VoiceFilter) -> Result<Vec<TtsVoice>, Error> {
+        let response = self
+            .http_client
+            .get(format!("{}/tts/list", self.config.base_url))
+            .query(&filter.query_params())
+            .send()
+            .await?
+            .error_for_api_status(&self.authenticated)
+            .await?
+            .bytes()
+            .await?;
+        let response = serde_json::from_slice::<TtsListResponse>(&response)
+            .map_err(|_| anyhow::anyhow!("Failed to deserialize models"))?;
+        let models = response.models.ok_or(anyhow::anyhow!(
+            "Invalid response body: missing 'models' property"
+        ))?;
+        Ok(models
+            .into_iter()
+            .filter(|voice| filter.matches(voice))
+            .collect())
+    }
+
+    #[cfg(feature = "voices")]
+    pub async fn model_details<S: Into<String>>(&self, model_token: S) -> Result<TtsVoice, Error> {
+        let response = self
+            .http_client
+            .get(format!(
+                "{}/tts/model/{}",
+                self.config.base_url,
+                model_token.into()
+            ))
+            .send()
+            .await?
+            .error_for_api_status(&self.authenticated)
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        let response = deserialize_response::<TtsModelResponse>(response)?;
+        Ok(response.model)
+    }
+
+    /// Fetches the model's details every `interval`, starting immediately, to follow changes such
+    /// as re-moderation. The stream ends once the model is removed and its details 404, other
+    /// request errors are yielded and the watch carries on.
+    #[cfg(feature = "voices")]
+    pub fn watch_model<S: Into<String>>(
+        &self,
+        model_token: S,
+        interval: Duration,
+    ) -> impl Stream<Item = Result<TtsVoice, Error>> + '_ {
+        // `None` once the watch can't go on, `bool` is whether the first fetch happened
+        let state = Some((model_token.into(), false));
+        futures::stream::unfold(state, move |state| async move {
+            let (model_token, fetched) = state?;
+            if fetched {
+                if let Err(e) = self.sleep(interval).await {
+                    return Some((Err(e), None));
+                }
+            }
+            match self.model_details(model_token.as_str()).await {
+                Err(Error::NotFound) => None,
+                result => Some((result, Some((model_token, true)))),
+            }
+        })
+    }
+
+    /// Checks a model token against a fresh cached voice list if there is one, or else the
+    /// model's detail endpoint.
+    #[cfg(feature = "voices")]
+    pub async fn model_exists<S: Into<String>>(&self, model_token: S) -> Result<bool, Error> {
+        let model_token = model_token.into();
+        {
+            let cache = self.voice_cache.lock().expect("voice cache lock poisoned");
+            if let Some(cache) = cache
+                .as_ref()
+                .filter(|cache| cache.is_fresh(self.config.voices_cache_ttl))
+            {
+                return Ok(cache
+                    .response
+                    .models
+                    .iter()
+                    .any(|voice| voice.model_token == model_token));
+            }
+        }
+        match self.model_details(model_token).await {
+            Ok(_) => Ok(true),
+            Err(Error::NotFound) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    #[cfg(all(feature = "voices", feature = "language_tags"))]
+    pub async fn voices_matching_language(
+        &self,
+        requested: &LanguageTag,
+    ) -> Result<Vec<TtsVoice>, Error> {
+        requested.parse()?;
+        let voices = self.voices().await?;
+        Ok(voices
+            .into_iter()
+            .filter(|voice| voice.matches_language(requested))
+            .collect())
+    }
+
+    #[cfg(feature = "voices")]
+    pub async fn voices_sorted_by_rating(&self) -> Result<Vec<TtsVoice>, Error> {
+        let mut voices = self.voices().await?;
+        voices.sort_by(|a, b| b.user_ratings.score().total_cmp(&a.user_ratings.score()));
+        Ok(voices)
+    }
+
+    #[cfg(feature = "voices")]
+    fn cache_voices(&self, response: VoicesResponse, etag: Option<String>) {
+        *self.voice_cache.lock().expect("voice cache lock poisoned") = Some(VoiceCache {
+            response,
+            etag,
+            fetched_at: Instant::now(),
+        });
+    }
+
+    /// Converts the uploaded audio `source_upload_token` to the voice of `model_token` and
+    /// waits for the result, polled like tts jobs.
+    #[cfg(feature = "voice_conversion")]
+    pub async fn voice_conversion<S: Into<String>>(
+        &self,
+        model_token: S,
+        source_upload_token: S,
+    ) -> Result<TtsResult, Error> {
+        self.voice_conversion_with(model_token, source_upload_token, RequestOptions::default())
+            .await
+    }
+
+    #[cfg(feature = "voice_conversion")]
+    pub async fn voice_conversion_with<S: Into<String>>(
+        &self,
+        model_token: S,
+        source_upload_token: S,
+        options: RequestOptions,
+    ) -> Result<TtsResult, Error> {
+        let voice_conversion_model_token = model_token.into();
+        let source_media_upload_token = source_upload_token.into();
+        let payload = VoiceConversionInferencePayload {
+            uuid_idempotency_token: options.idempotency_token_or(
+                self.config.idempotency_strategy,
+                IdempotencyInput::VoiceConversion {
+                    voice_conversion_model_token: &voice_conversion_model_token,
+                    source_media_upload_token: &source_media_upload_token,
+                },
+            ),
+            voice_conversion_model_token,
+            source_media_upload_token,
+        };
+        let inference_job_token = if self.config.dry_run {
+            dry_run_token()
+        } else {
+            let request = async {
+                let response = options
+                    .apply(self.http_client.post(format!(
+                        "{}/v1/voice_conversion/inference",
+                        self.config.base_url
+                    )))
+                    .json(&payload)
+                    .send()
+                    .await?
+                    .error_for_api_status(&self.authenticated)
+                    .await?
+                    .json::<serde_json::Value>()
+                    .await?;
+                deserialize_response::<VoiceConversionInferenceResponse>(response)
+            };
+            options.correlate(request.await)?.inference_job_token
+        };
+        self.poll_voice_conversion_job(&inference_job_token).await
+    }
+
+    /// Uploads `file` as the source audio of a conversion to `model`, after checking it has
+    /// the sample rate the model expects.
+    #[cfg(all(feature = "voice_conversion", feature = "face_animator"))]
+    pub async fn upload_voice_conversion_source(
+        &self,
+        model: &VoiceConversionModel,
+        file: &[u8],
+        options: RequestOptions,
+    ) -> Result<UploadFileResponse, Error> {
+        model.validate_sample_rate(file)?;
+        self.upload_audio_with(file, options).await
+    }
+
+    /// Converts the same upload to every voice in `model_tokens`, the results are in the same
+    /// order as the models.
+    #[cfg(feature = "voice_conversion")]
+    pub async fn voice_conversion_batch(
+        &self,
+        source_upload_token: &str,
+        model_tokens: Vec<ModelToken>,
+        concurrency: usize,
+    ) -> Vec<Result<TtsResult, Error>> {
+        futures::stream::iter(model_tokens)
+            .map(|model_token| self.voice_conversion(model_token, source_upload_token.to_string()))
+            .buffered(concurrency.max(1))
+            .collect()
+            .await
+    }
+
+    #[cfg(feature = "voice_conversion")]
+    async fn poll_voice_conversion_job(
+        &self,
+        inference_job_token: &str,
+    ) -> Result<TtsResult, Error> {
+        if is_dry_run_token(inference_job_token) {
+            return Ok(TtsResult {
+                job_token: inference_job_token.to_string(),
+                audio_url: self.request_file_url(&format!("/dry_run/{inference_job_token}.wav")),
+                maybe_spectrogram_url: None,
+                maybe_duration: None,
+                maybe_inference_text: None,
+            });
+        }
+        self.cancellable_poll(inference_job_token.to_string(), async move {
+            let mut schedule = PollSchedule::new(&self.config.tts_poll_config);
+            loop {
+                let response = self
+                    .http_client
+                    .get(format!("{}/model_inference/job_status/{inference_job_token}", self.config.base_url
+                    ))
+                    .send()
+                    .await?
+                    .error_for_api_status(&self.authenticated)
+                    .await?
+                    .json::<serde_json::Value>()
+                    .await?;
+                let response = deserialize_job_response::<VoiceConversionJobResponse>(response)?;
+                let state = response.state;
+                if !response.success || state.status.status.is_failure() {
+                    return Err(Error::VoiceConversionJobFailed {
+                        job_token: self.error_token(state.job_token),
+                        status: state.status.status,
+                    });
+                }
+                if state.status.status.is_terminal() {
+                    let audio_path = state
+                        .maybe_result
+                        .and_then(|result| result.maybe_public_bucket_media_path)
+                        .ok_or(anyhow::anyhow!(
+                            "Invalid response body: missing 'maybe_public_bucket_media_path' property"
+                        ))?;
+                    return Ok(TtsResult {
+                        job_token: state.job_token,
+                        audio_url: self.request_file_url(&audio_path),
+                        maybe_spectrogram_url: None,
+                        maybe_duration: None,
+                        maybe_inference_text: None,
+                    });
+                }
+                if let Some(polls) = schedule.record_poll() {
+                    return Err(Error::PollLimitExceeded {
+                        job_token: self.error_token(state.job_token),
+                        polls,
+                    });
+                }
+                self.sleep(schedule.next_interval()).await?;
+            }
+        })
+        .await
+    }
+
+    #[cfg(feature = "voice_conversion")]
+    pub async fn voice_conversion_models(&self) -> Result<Vec<VoiceConversionModel>, Error> {
+        let response = self
+            .http_client
+            .get(format!(
+                "{}/v1/voice_conversion/model_list",
+                self.config.base_url
+            ))
+            .send()
+            .await?
+            .error_for_api_status(&self.authenticated)
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        let response = response.get("models").ok_or(anyhow::anyhow!(
+            "Invalid response body: missing 'models' property"
+        ))?;
+        let response = serde_json::from_value(response.to_owned())
+            .map_err(|_| anyhow::anyhow!("Failed to deserialize voice conversion models"))?;
+        Ok(response)
+    }
+
+    #[cfg(feature = "leaderboard")]
+    pub async fn leaderboard(&self, kind: LeaderboardKind) -> Result<Vec<LeaderboardEntry>, Error> {
+        let response = self
+            .http_client
+            .get(format!("{}/leaderboard", self.config.base_url))
+            .send()
+            .await?
+            .error_for_api_status(&self.authenticated)
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        let response = deserialize_response::<LeaderboardResponse>(response)?;
+        Ok(match kind {
+            LeaderboardKind::Tts => response.tts_leaderboard,
+            LeaderboardKind::FaceAnimation => response.w2l_leaderboard,
+        })
+    }
+
+    #[cfg(feature = "voices")]
+    pub async fn my_models(&self) -> Result<Vec<TtsVoice>, Error> {
+        let Some(user) = self.session().await?.user else {
+            self.authenticated.store(false, Ordering::Relaxed);
+            return Err(Error::AuthenticationError);
+        };
+        let username = user.username;
+        let response = self
+            .http_client
+            .get(format!(
+                "{}/user/{username}/tts_models",
+                self.config.base_url
+            ))
+            .send()
+            .await?
+            .error_for_api_status(&self.authenticated)
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        let response = response.get("tts_models").ok_or(anyhow::anyhow!(
+            "Invalid response body: missing 'tts_models' property"
+        ))?;
+        let response = serde_json::from_value(response.to_owned())
+            .map_err(|_| anyhow::anyhow!("Failed to deserialize models"))?;
+        Ok(response)
+    }
+
+    #[cfg(feature = "model_management")]
+    pub async fn create_tts_model(&self, payload: CreateModelPayload) -> Result<String, Error> {
+        if self.config.dry_run {
+            return Ok(dry_run_token());
+        }
+        let response = self
+            .http_client
+            .post(format!("{}/v1/tts/model/create", self.config.base_url))
+            .json(&payload)
+            .send()
+            .await?
+            .error_for_api_status(&self.authenticated)
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        let response = deserialize_response::<CreateModelResponse>(response)?;
+        Ok(response.model_token)
+    }
+
+    #[cfg(feature = "face_animator")]
+    pub async fn upload_audio(&self, file: &[u8]) -> Result<UploadFileResponse, Error> {
+        self.upload_audio_with(file, RequestOptions::default())
+            .await
+    }
+
+    #[cfg(feature = "face_animator")]
+    pub async fn upload_audio_with(
+        &self,
+        file: &[u8],
+        options: RequestOptions,
+    ) -> Result<UploadFileResponse, Error> {
+        self.upload_file("upload_audio", file, UploadSource::File, options)
+            .await
+    }
+
+    /// Like `upload_audio_with`, but streams `len` bytes from `reader` instead of buffering
+    /// them. A stream can't be hashed up front, so without an explicit idempotency token in
+    /// `options` the upload gets a random one, even with a deterministic strategy.
+    #[cfg(feature = "face_animator")]
+    pub async fn upload_audio_stream<R>(
+        &self,
+        reader: R,
+        len: u64,
+        mime: &str,
+        options: RequestOptions,
+    ) -> Result<UploadFileResponse, Error>
+    where
+        R: AsyncRead + Send + Sync + Unpin + 'static,
+    {
+        if len > self.config.max_upload_size as u64 {
+            return Err(Error::UploadTooLarge {
+                size: len as usize,
+                max: self.config.max_upload_size,
+            });
+        }
+        if self.config.dry_run {
+            return Ok(UploadFileResponse {
+                success: true,
+                upload_token: dry_run_token(),
+            });
+        }
+        let body = futures::stream::try_unfold(reader, |mut reader| async move {
+            let mut chunk = vec![0; UPLOAD_STREAM_CHUNK_SIZE];
+            let read = reader.read(&mut chunk).await?;
+            if read == 0 {
+                return Ok(None);
+            }
+            chunk.truncate(read);
+            Ok::<_, std::io::Error>(Some((Bytes::from(chunk), reader)))
+        });
+        let file = Part::stream_with_length(Body::wrap_stream(body), len)
+            .file_name("audio")
+            .mime_str(mime)?;
+        let uuid_idempotency_token = options.idempotency_token.unwrap_or_else(Uuid::new_v4);
+        let form = Form::new()
+            .text("uuid_idempotency_token", uuid_idempotency_token.to_string())
+            .text("source", UploadSource::File.as_str())
+            .part("file", file);
+        let request = async {
+            let response = options
+                .apply(self.http_client.post(format!(
+                    "{}/media_uploads/upload_audio",
+                    self.config.base_url
+                )))
+                .multipart(form)
+                .send()
+                .await?
+                .error_for_api_status(&self.authenticated)
+                .await?
+                .json::<serde_json::Value>()
+                .await?;
+            deserialize_response::<UploadFileResponse>(response)
+        };
+        options.correlate(request.await)
+    }
+
+    #[cfg(feature = "face_animator")]
+    pub async fn upload_image(&self, file: &[u8]) -> Result<UploadFileResponse, Error> {
+        self.upload_image_with(file, RequestOptions::default())
+            .await
+    }
+
+    #[cfg(feature = "face_animator")]
+    pub async fn upload_image_with(
+        &self,
+        file: &[u8],
+        options: RequestOptions,
+    ) -> Result<UploadFileResponse, Error> {
+        self.upload_file("upload_image", file, UploadSource::File, options)
+            .await
+    }
+
+    #[cfg(feature = "face_animator")]
+    pub async fn media_upload_status<S: Into<String>>(
+        &self,
+        upload_token: S,
+    ) -> Result<MediaUploadState, Error> {
+        let upload_token = upload_token.into();
+        if is_dry_run_token(&upload_token) {
+            return Ok(MediaUploadState::dry_run(upload_token));
+        }
+        let response = self
+            .http_client
+            .get(format!(
+                "{}/media_uploads/get/{upload_token}",
+                self.config.base_url
+            ))
+            .send()
+            .await?
+            .error_for_api_status(&self.authenticated)
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        let response = deserialize_response::<MediaUploadStatusResponse>(response)?;
+        Ok(response.media_upload)
+    }
+
+    /// Polls an upload until it has been processed and can be used in an animation.
+    #[cfg(feature = "face_animator")]
+    pub async fn wait_for_media_upload<S: Into<String> + Copy>(
+        &self,
+        upload_token: S,
+    ) -> Result<MediaUploadState, Error> {
+        self.cancellable_poll(upload_token.into(), async move {
+            let mut schedule = PollSchedule::new(&self.config.face_animation_poll_config);
+            loop {
+                let state = self.media_upload_status(upload_token).await?;
+                if state.is_ready() {
+                    return Ok(state);
+                }
+                if let Some(polls) = schedule.record_poll() {
+                    return Err(Error::PollLimitExceeded {
+                        job_token: self.error_token(state.token),
+                        polls,
+                    });
+                }
+                self.sleep(schedule.next_interval()).await?;
+            }
+        })
+        .await
+    }
+
+    #[cfg(feature = "face_animator")]
+    async fn upload_file(
+        &self,
+        endpoint: &str,
+        file: &[u8],
+        source: UploadSource,
+        options: RequestOptions,
+    ) -> Result<UploadFileResponse, Error> {
+        if file.len() > self.config.max_upload_size {
+            return Err(Error::UploadTooLarge {
+                size: file.len(),
+                max: self.config.max_upload_size,
+            });
+        }
+        let payload = UploadFilePayload {
+            uuid_idempotency_token: options.idempotency_token_or(
+                self.config.idempotency_strategy,
+                IdempotencyInput::Upload { endpoint, file },
+            ),
+            file,
+            source,
+        };
+        if self.config.dry_run {
+            return Ok(UploadFileResponse {
+                success: true,
+                upload_token: dry_run_token(),
+            });
+        }
+        // the file can't be url encoded, the api expects a multipart form like browsers send
+        let form = Form::new()
+            .text(
+                "uuid_idempotency_token",
+                payload.uuid_idempotency_token.to_string(),
+            )
+            .text("source", payload.source.as_str())
+            .part(
+                "file",
+                Part::bytes(payload.file.to_vec())
+                    .file_name(endpoint.trim_start_matches("upload_").to_string()),
+            );
+        let request = async {
+            let response = options
+                .apply(
+                    self.http_client
+                        .post(format!("{}/media_uploads/{endpoint}", self.config.base_url)),
+                )
+                .multipart(form)
+                .send()
+                .await?
+                .error_for_api_status(&self.authenticated)
+                .await?
+                .json::<serde_json::Value>()
+                .await?;
+            deserialize_response::<UploadFileResponse>(response)
+        };
+        options.correlate(request.await)
+    }
+
+    /// The `dimensions` presets accepted by `create_facial_animation`, as published by the api.
+    /// Falls back to the presets known to this crate if the api doesn't publish them.
+    #[cfg(feature = "face_animator")]
+    pub async fn face_animation_dimensions(&self) -> Result<Vec<String>, Error> {
+        let response = self
+            .http_client
+            .get(format!(
+                "{}/animation/face_animation/dimensions",
+                self.config.base_url
+            ))
+            .send()
+            .await?
+            .error_for_api_status(&self.authenticated)
+            .await;
+        let response = match response {
+            Ok(response) => response.json::<serde_json::Value>().await?,
+            Err(Error::NotFound) => {
+                return Ok(FACE_ANIMATION_DIMENSIONS
+                    .iter()
+                    .map(|dimensions| dimensions.to_string())
+                    .collect())
+            }
+            Err(e) => return Err(e),
+        };
+        Ok(deserialize_response::<FaceAnimationDimensionsResponse>(response)?.dimensions)
+    }
+
+    /// Checks the client's `default_face_animation_dimensions` is one of the presets returned
+    /// by `face_animation_dimensions`, so a preset the server dropped is caught up front.
+    #[cfg(feature = "face_animator")]
+    pub async fn validate_default_face_animation_dimensions(&self) -> Result<(), Error> {
+        let dimensions = &self.config.default_face_animation_dimensions;
+        if !self.face_animation_dimensions().await?.contains(dimensions) {
+            return Err(Error::UnknownFaceAnimationDimensions {
+                dimensions: dimensions.clone(),
+            });
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "face_animator")]
+    #[deprecated(
+        note = "use `CreateFaceAnimationPayload::builder` instead, it doesn't need an `.await`"
+    )]
+    pub async fn create_facial_animation_builder(&self) -> CreateFaceAnimationPayloadBuilder {
+        CreateFaceAnimationPayload::builder()
+    }
+
+    #[cfg(feature = "face_animator")]
+    pub async fn create_facial_animation(
+        &self,
+        payload: CreateFaceAnimationPayload,
+    ) -> Result<CreateFaceAnimationResponse, Error> {
+        self.create_facial_animation_with(payload, RequestOptions::default())
+            .await
+    }
+
+    #[cfg(feature = "face_animator")]
+    pub async fn create_facial_animation_with(
+        &self,
+        mut payload: CreateFaceAnimationPayload,
+        options: RequestOptions,
+    ) -> Result<CreateFaceAnimationResponse, Error> {
+        if let Some(idempotency_token) = options.idempotency_token {
+            payload.uuid_idempotency_token = idempotency_token;
+        }
+        payload
+            .dimensions
+            .get_or_insert_with(|| self.config.default_face_animation_dimensions.clone());
+        if self.config.dry_run {
+            return Ok(CreateFaceAnimationResponse {
+                success: true,
+                inference_job_token: dry_run_token(),
+            });
+        }
+        if payload.remove_watermark
+            && self.config.check_subscription_features
+            && !self.has_active_subscription().await?
+        {
+            return Err(Error::FeatureRequiresSubscription {
+                feature: "remove_watermark",
+            });
+        }
+        let request = async {
+            let response = options
+                .apply(self.http_client.post(format!(
+                    "{}/animation/face_animation/create",
+                    self.config.base_url
+                )))
+                .json(&payload)
+                .send()
+                .await?
+                .error_for_api_status(&self.authenticated)
+                .await?
+                .json::<serde_json::Value>()
+                .await?;
+            deserialize_response::<CreateFaceAnimationResponse>(response)
+        };
+        options.correlate(request.await)
+    }
+
+    // only a known free account fails the check, an account without billing info (a 404) lets
+    // it through. Other failures are returned, so an expired session doesn't pass unnoticed
+    #[cfg(feature = "face_animator")]
+    async fn has_active_subscription(&self) -> Result<bool, Error> {
+        let response = self
+            .http_client
+            .get(format!(
+                "{}/v1/billing/active_subscriptions",
+                self.config.base_url
+            ))
+            .send()
+            .await?
+            .error_for_api_status(&self.authenticated)
+            .await;
+        let response = match response {
+            Ok(response) => response.json::<ActiveSubscriptionsResponse>().await?,
+            Err(Error::NotFound) => return Ok(true),
+            Err(e) => return Err(e),
+        };
+        Ok(response
+            .active_subscriptions
+            .is_none_or(|subscriptions| !subscriptions.is_empty()))
+    }
+
+    #[cfg(feature = "face_animator")]
+    pub async fn animate_face(
+        &self,
+        audio: &[u8],
+        image: &[u8],
+        mut payload_builder: CreateFaceAnimationPayloadBuilder,
+    ) -> Result<FaceAnimationJobResponse, Error> {
+        // uploads are independent, if either fails the other is dropped and no job is created
+        let (audio_upload, image_upload) =
+            futures::try_join!(self.upload_audio(audio), self.upload_image(image))?;
+        let payload = payload_builder
+            .audio_sorce(audio_upload.upload_token)
+            .image_source(image_upload.upload_token)
+            .build()
+            .map_err(|e| anyhow::anyhow!("Invalid face animation payload: {e}"))?;
+        let response = self.create_facial_animation(payload).await?;
+        self.poll_face_animation_job(response.inference_job_token.as_str())
+            .await
+    }
+
+    /// Like `poll_tts_job`, dropping the future stops polling but not the job. A job that
+    /// requires a keepalive is reaped by the server once polling stops.
+    #[cfg(feature = "face_animator")]
+    pub async fn poll_face_animation_job<T: Into<String> + Copy>(
+        &self,
+        inference_token: T,
+    ) -> Result<FaceAnimationJobResponse, Error> {
+        let job_token = inference_token.into();
+        if is_dry_run_token(&job_token) {
+            return Ok(FaceAnimationJobResponse::dry_run(job_token));
+        }
+        self.cancellable_poll(job_token, async move {
+            let mut schedule = PollSchedule::new(&self.config.face_animation_poll_config);
+            loop {
+                let response = self
+                    .http_client
+                    .get(format!(
+                        "{}/model_inference/job_status/{}",
+                        self.config.base_url,
+                        inference_token.into()
+                    ))
+                    .send()
+                    .await?
+                    .error_for_api_status(&self.authenticated)
+                    .await?
+                    .json::<serde_json::Value>()
+                    .await?;
+                let response = deserialize_job_response::<FaceAnimationJobResponse>(response)?;
+                if !response.success || response.state.status.status.is_failure() {
+                    return Err(self.face_animation_job_failed(response));
+                }
+                if response.state.status.status.is_terminal() {
+                    return Ok(response);
+                }
+                if let Some(polls) = schedule.record_poll() {
+                    return Err(Error::PollLimitExceeded {
+                        job_token: self.error_token(response.state.job_token),
+                        polls,
+                    });
+                }
+                // jobs that require a keepalive get reaped if the client stops pinging them,
+                // a keepalive that still fails after retrying is skipped rather than ending
+                // the poll, the next one can still land before the job is reaped
+                if response.state.status.require_keepalive {
+                    let policy =
+                        RetryPolicy::new(KEEPALIVE_RETRIES + 1).backoff(KEEPALIVE_RETRY_BACKOFF);
+                    if let Err(_e) = self
+                        .retry(&policy, || self.face_animation_keepalive(inference_token))
+                        .await
+                    {
+                        #[cfg(feature = "tracing")]
+                        tracing::warn!(error = %_e, "face animation keepalive failed");
+                    }
+                }
+                // sleep before making next request to prevent 429 errors
+                self.sleep(schedule.next_interval()).await?;
+            }
+        })
+        .await
+    }
+
+    /// Like `tts_job_raw`, for face animation jobs.
+    #[cfg(feature = "face_animator")]
+    pub async fn face_animation_job_raw<T: Into<String>>(
+        &self,
+        inference_token: T,
+    ) -> Result<serde_json::Value, Error> {
+        let response = self
+            .http_client
+            .get(format!(
+                "{}/model_inference/job_status/{}",
+                self.config.base_url,
+                inference_token.into()
+            ))
+            .send()
+            .await?
+            .error_for_api_status(&self.authenticated)
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        Ok(response)
+    }
+
+    #[cfg(feature = "face_animator")]
+    async fn face_animation_keepalive<T: Into<String>>(
+        &self,
+        inference_token: T,
+    ) -> Result<(), Error> {
+        if self.config.dry_run {
+            return Ok(());
+        }
+        self.http_client
+            .post(format!(
+                "{}/model_inference/job_keepalive/{}",
+                self.config.base_url,
+                inference_token.into()
+            ))
+            .send()
+            .await?
+            .error_for_api_status(&self.authenticated)
+            .await?;
+        Ok(())
+    }
+
+    #[cfg(feature = "face_animator")]
+    fn face_animation_job_failed(&self, response: FaceAnimationJobResponse) -> Error {
+        let state = response.state;
+        Error::FaceAnimationJobFailed {
+            job_token: self.error_token(state.job_token),
+            status: state.status.status,
+            maybe_failure_category: state.status.maybe_failure_category,
+            maybe_extra_status_description: state.status.maybe_extra_status_description,
+        }
+    }
+
+    #[cfg_attr(
+        not(any(
+            feature = "tts",
+            feature = "face_animator",
+            feature = "voice_conversion"
+        )),
+        allow(dead_code)
+    )]
+    fn error_token(&self, token: String) -> String {
+        if !self.config.redact_error_tokens {
+            return token;
+        }
+        let visible = token
+            .char_indices()
+            .rev()
+            .nth(REDACTED_TOKEN_VISIBLE_CHARS - 1);
+        match visible {
+            Some((index, _)) if index > 0 => format!("…{}", &token[index..]),
+            _ => token,
+        }
+    }
+}
+
+// `bytes <start>-<end>/<len>` on a 206 and `bytes */<len>` on a 416, either may be absent
+#[cfg(feature = "tokio")]
+#[derive(Clone, Copy, Debug)]
+struct ContentRange {
+    start: Option<u64>,
+    len: Option<u64>,
+}
+
+#[cfg(feature = "tokio")]
+impl ContentRange {
+    fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let range = headers
+            .get(CONTENT_RANGE)?
+            .to_str()
+            .ok()?
+            .strip_prefix("bytes ")?;
+        let (range, len) = range.split_once('/')?;
+        Some(ContentRange {
+            start: range
+                .split_once('-')
+                .and_then(|(start, _)| start.trim().parse().ok()),
+            len: len.trim().parse().ok(),
+        })
+    }
+}
+
+// appends `-1`, `-2`, ... before the extension until the name is free on disk and in `taken`
+#[cfg(feature = "tokio")]
+async fn unique_path(
+    dir: &Path,
+    name: &str,
+    taken: &mut HashSet<PathBuf>,
+) -> Result<PathBuf, Error> {
+    let name = Path::new(name);
+    let stem = name
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let extension = name
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+    let mut path = dir.join(format!("{stem}{extension}"));
+    let mut suffix = 0;
+    while taken.contains(&path)
+        || tokio::fs::try_exists(&path)
+            .await
+            .map_err(anyhow::Error::from)?
+    {
+        suffix += 1;
+        path = dir.join(format!("{stem}-{suffix}{extension}"));
+    }
+    taken.insert(path.clone());
+    Ok(path)
+}
+
+#[cfg(any(
+    feature = "tts",
+    feature = "face_animator",
+    feature = "model_management",
+    feature = "voice_conversion"
+))]
+fn dry_run_token() -> String {
+    format!("{DRY_RUN_TOKEN_PREFIX}{}", Uuid::new_v4())
+}
+
+#[cfg(any(
+    feature = "tts",
+    feature = "face_animator",
+    feature = "voice_conversion"
+))]
+fn is_dry_run_token(token: &str) -> bool {
+    token.starts_with(DRY_RUN_TOKEN_PREFIX)
+}
+
+#[cfg(feature = "tts")]
+fn inference_job_token(response: TtsInferenceResponse) -> Result<String, Error> {
+    response.inference_job_token.ok_or_else(|| {
+        anyhow::anyhow!(
+            "Tts inference was unsuccessful: {}",
+            response
+                .error_reason
+                .or(response.error_message)
+                .unwrap_or_default()
+        )
+        .into()
+    })
+}
+
+// `error_for_status` drops the body, so client errors would lose the api's error envelope.
+// A 401 means the session is gone, so it also clears the client's `authenticated` flag.
+trait ResponseExt: Sized {
+    async fn error_for_api_status(self, authenticated: &AtomicBool) -> Result<Self, Error>;
+}
+
+impl ResponseExt for Response {
+    async fn error_for_api_status(self, authenticated: &AtomicBool) -> Result<Self, Error> {
+        let error = match self.error_for_status_ref() {
+            Ok(_) => return Ok(self),
+            Err(e) => Error::from(e),
+        };
+        if matches!(error, Error::AuthenticationError) {
+            authenticated.store(false, Ordering::Relaxed);
+        }
+        // 401, 404 and 429 keep their dedicated variants, server errors stay retryable
+        if !matches!(&error, Error::Http { status, .. } if status.is_client_error()) {
+            return Err(error);
+        }
+        let envelope = self
+            .json::<serde_json::Value>()
+            .await
+            .ok()
+            .as_ref()
+            .and_then(parse_error_envelope);
+        Err(envelope.unwrap_or(error))
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct ApiErrorEnvelope {
+    success: bool,
+    error_type: Option<String>,
+    error_message: Option<String>,
+    error_reason: Option<String>,
+}
+
+fn parse_error_envelope(response: &serde_json::Value) -> Option<Error> {
+    let envelope = ApiErrorEnvelope::deserialize(response).ok()?;
+    if envelope.success {
+        return None;
+    }
+    Some(Error::ApiError {
+        error_type: envelope.error_type,
+        message: envelope
+            .error_message
+            .or(envelope.error_reason)
+            .unwrap_or_else(|| "unknown error".to_string()),
+    })
+}
+
+#[cfg_attr(
+    not(any(
+        feature = "tts",
+        feature = "face_animator",
+        feature = "model_management",
+        feature = "voice_conversion"
+    )),
+    allow(dead_code)
+)]
+fn deserialize_response<T: DeserializeOwned>(response: serde_json::Value) -> Result<T, Error> {
+    if let Some(e) = parse_error_envelope(&response) {
+        return Err(e);
+    }
+    serde_json::from_value(response)
+        .map_err(|e| anyhow::anyhow!("Failed to deserialize response: {e}").into())
+}
+
+// the api may answer with a 200 and an error envelope instead of a job state
+#[cfg(any(
+    feature = "tts",
+    feature = "face_animator",
+    feature = "voice_conversion"
+))]
+fn deserialize_job_response<T: DeserializeOwned>(response: serde_json::Value) -> Result<T, Error> {
+    if response.get("state").is_none() {
+        return Err(
+            parse_error_envelope(&response).unwrap_or_else(|| Error::ApiError {
+                error_type: None,
+                message: "missing job state".to_string(),
+            }),
+        );
+    }
+    serde_json::from_value(response)
+        .map_err(|e| anyhow::anyhow!("Failed to deserialize job response: {e}").into())
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct SessionResponse {
+    logged_in: bool,
+    #[cfg_attr(not(feature = "voices"), allow(dead_code))]
+    user: Option<SessionUser>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct SessionUser {
+    #[cfg_attr(not(feature = "voices"), allow(dead_code))]
+    username: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct TtsInferencePayload {
+    uuid_idempotency_token: Uuid,
+    tts_model_token: String,
+    inference_text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pitch: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    speed: Option<f32>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(from = "RawTtsInferenceResponse")]
+pub struct TtsInferenceResponse {
+    pub success: bool,
+    pub error_type: Option<String>,
+    pub error_message: Option<String>,
+    pub error_reason: Option<String>,
+    pub inference_job_token: Option<String>,
+    pub inference_job_token_type: Option<String>,
+    /// Whether the idempotency token was already used, `inference_job_token` is then the
+    /// job created by the original submission.
+    pub was_replay: bool,
+}
+
+#[derive(Deserialize)]
+struct RawTtsInferenceResponse {
+    success: bool,
+    #[serde(default)]
+    error_type: Option<String>,
+    #[serde(default)]
+    error_message: Option<String>,
+    #[serde(default)]
+    error_reason: Option<String>,
+    #[serde(default)]
+    inference_job_token: Option<String>,
+    #[serde(default)]
+    inference_job_token_type: Option<String>,
+    #[serde(default, alias = "original_inference_job_token")]
+    maybe_original_inference_job_token: Option<String>,
+}
+
+impl From<RawTtsInferenceResponse> for TtsInferenceResponse {
+    fn from(raw: RawTtsInferenceResponse) -> Self {
+        match (
+            raw.inference_job_token,
+            raw.maybe_original_inference_job_token,
+        ) {
+            (None, Some(original)) => TtsInferenceResponse {
+                success: true,
+                error_type: None,
+                error_message: None,
+                error_reason: None,
+                inference_job_token: Some(original),
+                inference_job_token_type: raw.inference_job_token_type,
+                was_replay: true,
+            },
+            (inference_job_token, maybe_original) => TtsInferenceResponse {
+                success: raw.success,
+                error_type: raw.error_type,
+                error_message: raw.error_message,
+                error_reason: raw.error_reason,
+                inference_job_token,
+                inference_job_token_type: raw.inference_job_token_type,
+                was_replay: maybe_original.is_some(),
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ResponseHeaders {
+    pub request_id: Option<String>,
+    pub cf_ray: Option<String>,
+    pub rate_limit_limit: Option<u64>,
+    pub rate_limit_remaining: Option<u64>,
+    pub rate_limit_reset: Option<u64>,
+    // when the headers arrived, a relative reset counts down from here
+    pub(crate) received_at: Option<Instant>,
+}
+
+impl ResponseHeaders {
+    /// How long to wait before the next request so the rate limit isn't hit. Waits until the
+    /// reset once no requests are remaining, and spreads the remaining requests evenly until
+    /// the reset once fewer than a tenth of `X-RateLimit-Limit` are left.
+    /// `X-RateLimit-Reset` is read as a unix timestamp, or as seconds from when the response
+    /// arrived when it is too small to be one.
+    pub fn rate_limit_wait(&self) -> Option<Duration> {
+        let remaining = self.rate_limit_remaining?;
+        let low = match self.rate_limit_limit {
+            Some(limit) => (remaining as f64) < limit as f64 * RATE_LIMIT_LOW_FRACTION,
+            None => false,
+        };
+        if remaining > 0 && !low {
+            return None;
+        }
+        let until_reset = self.until_rate_limit_reset()?;
+        let remaining = u32::try_from(remaining).unwrap_or(u32::MAX);
+        Some(until_reset / remaining.saturating_add(1))
+    }
+
+    fn until_rate_limit_reset(&self) -> Option<Duration> {
+        let reset = self.rate_limit_reset?;
+        if reset < RATE_LIMIT_RESET_EPOCH_THRESHOLD {
+            let elapsed = self
+                .received_at
+                .map(|received_at| received_at.elapsed())
+                .unwrap_or_default();
+            return Some(Duration::from_secs(reset).saturating_sub(elapsed));
+        }
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .ok()?
+            .as_secs();
+        Some(Duration::from_secs(reset.saturating_sub(now)))
+    }
+}
+
+impl From<&HeaderMap> for ResponseHeaders {
+    fn from(headers: &HeaderMap) -> Self {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+        let number = |name: &str| header(name).and_then(|value| value.parse().ok());
+        ResponseHeaders {
+            request_id: header("x-request-id"),
+            cf_ray: header("cf-ray"),
+            rate_limit_limit: number("x-ratelimit-limit"),
+            rate_limit_remaining: number("x-ratelimit-remaining"),
+            rate_limit_reset: number("x-ratelimit-reset"),
+            received_at: Some(Instant::now()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct TtsJobResponse {
+    pub success: bool,
+    pub state: TtsJobState,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct TtsJobState {
     pub status: JobStatus,
     pub job_token: String,
-    pub maybe_public_bucket_wav_audio_path: Option<String>,
+    #[serde(default)]
+    pub maybe_public_bucket_wav_audio_path: Option<String>,
+    /// Only returned by some model types.
+    #[serde(default)]
+    pub maybe_public_bucket_spectrogram_path: Option<String>,
+    #[serde(default)]
+    pub maybe_assigned_worker: Option<String>,
+    #[serde(default)]
+    pub maybe_assigned_cluster: Option<String>,
+    #[serde(default)]
+    pub attempt_count: u32,
+    #[serde(default)]
+    pub maybe_duration_millis: Option<u64>,
+    #[serde(default)]
+    pub maybe_recommended_poll_interval_ms: Option<u64>,
+}
+
+#[cfg(feature = "tts")]
+impl TtsJobResponse {
+    // a completed job for a token returned by a dry run, its audio doesn't exist
+    fn dry_run(job_token: String) -> Self {
+        TtsJobResponse {
+            success: true,
+            state: TtsJobState {
+                status: JobStatus::CompleteSuccess,
+                maybe_public_bucket_wav_audio_path: Some(format!("/dry_run/{job_token}.wav")),
+                job_token,
+                maybe_public_bucket_spectrogram_path: None,
+                maybe_assigned_worker: None,
+                maybe_assigned_cluster: None,
+                attempt_count: 0,
+                maybe_duration_millis: None,
+                maybe_recommended_poll_interval_ms: None,
+            },
+        }
+    }
+}
+
+impl TtsJobState {
+    /// The api doesn't say whether a failed job is worth resubmitting, so this is inferred:
+    /// a `Dead` job that was given up on before using all of its `MAX_JOB_ATTEMPTS` attempts
+    /// is considered retryable, while a `CompleteFailure` or an exhausted job is permanent.
+    pub fn is_retryable(&self) -> bool {
+        self.status == JobStatus::Dead && self.attempt_count < MAX_JOB_ATTEMPTS
+    }
+}
+
+pub trait JobLifecycle {
+    fn status(&self) -> &JobStatus;
+
+    fn job_token(&self) -> &str;
+
+    fn is_complete(&self) -> bool {
+        self.status().is_terminal()
+    }
+}
+
+impl JobLifecycle for TtsJobState {
+    fn status(&self) -> &JobStatus {
+        &self.status
+    }
+
+    fn job_token(&self) -> &str {
+        &self.job_token
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TtsResult {
+    pub job_token: String,
+    pub audio_url: String,
+    pub maybe_spectrogram_url: Option<String>,
+    pub maybe_duration: Option<Duration>,
+    /// The text that was submitted, only known when the job was started by this call.
+    pub maybe_inference_text: Option<String>,
+}
+
+#[cfg(feature = "tts")]
+#[derive(Clone, Debug)]
+pub struct TimedResult {
+    pub result: TtsResult,
+    pub submit_to_complete: Duration,
+}
+
+#[cfg(feature = "tts")]
+#[derive(Clone, Debug, Deserialize)]
+struct TtsHistoryResponse {
+    results: Vec<TtsHistoryEntry>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct TtsHistoryEntry {
+    pub tts_result_token: String,
+    pub tts_model_token: String,
+    #[serde(default)]
+    pub tts_model_title: Option<String>,
+    pub raw_inference_text: String,
+    pub public_bucket_wav_audio_path: String,
+    /// The full url of `public_bucket_wav_audio_path`.
+    #[serde(skip)]
+    pub audio_url: String,
+    #[serde(default)]
+    pub maybe_creator_username: Option<String>,
+    #[serde(default)]
+    pub duration_millis: Option<u64>,
+    pub created_at: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CostEstimate {
+    pub characters: usize,
+    pub maybe_credits: Option<f64>,
+    pub estimated_duration: Duration,
+}
+
+#[cfg(feature = "tts")]
+#[derive(Clone, Debug, Deserialize)]
+struct TtsResultDetailResponse {
+    result: TtsResultDetail,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct TtsResultDetail {
+    pub tts_result_token: String,
+    pub raw_inference_text: String,
+    pub tts_model_token: String,
+    #[serde(default)]
+    pub tts_model_title: Option<String>,
+    pub public_bucket_wav_audio_path: String,
+    #[serde(default)]
+    pub maybe_creator_username: Option<String>,
+    #[serde(default)]
+    pub duration_millis: Option<u64>,
+    pub created_at: String,
+}
+
+/// Emitted by `Client::download_results_stream`, `index` is the position of the result.
+#[derive(Debug)]
+pub enum DownloadEvent {
+    Started {
+        index: usize,
+        maybe_total: Option<u64>,
+    },
+    Progress {
+        index: usize,
+        downloaded: u64,
+        maybe_total: Option<u64>,
+    },
+    Completed {
+        index: usize,
+        audio: Vec<u8>,
+    },
+    Failed {
+        index: usize,
+        error: Error,
+    },
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all(deserialize = "snake_case"))]
+pub enum JobStatus {
+    AttemptFailed,
+    CompleteFailure,
+    CompleteSuccess,
+    Dead,
+    Pending,
+    Started,
+}
+
+impl JobStatus {
+    pub fn all() -> &'static [JobStatus] {
+        &[
+            JobStatus::AttemptFailed,
+            JobStatus::CompleteFailure,
+            JobStatus::CompleteSuccess,
+            JobStatus::Dead,
+            JobStatus::Pending,
+            JobStatus::Started,
+        ]
+    }
+
+    /// Whether the job has stopped and won't change status anymore.
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            JobStatus::CompleteSuccess | JobStatus::CompleteFailure | JobStatus::Dead
+        )
+    }
+
+    /// Whether the job has stopped without a result, `AttemptFailed` is still being retried.
+    pub fn is_failure(&self) -> bool {
+        matches!(self, JobStatus::CompleteFailure | JobStatus::Dead)
+    }
+
+    /// Whether the job is waiting in the queue for a worker.
+    pub fn is_queued(&self) -> bool {
+        matches!(self, JobStatus::Pending)
+    }
+
+    /// Whether a worker is running the job right now.
+    pub fn is_processing(&self) -> bool {
+        matches!(self, JobStatus::Started)
+    }
+}
+
+#[derive(Debug, Default)]
+struct ActivePolls {
+    next_id: u64,
+    handles: HashMap<u64, AbortHandle>,
+}
+
+// removes the poll's abort handle once it finishes or is dropped
+struct ActivePollGuard<'a> {
+    active_polls: &'a Mutex<ActivePolls>,
+    id: u64,
+}
+
+impl<'a> ActivePollGuard<'a> {
+    #[cfg_attr(
+        not(any(
+            feature = "tts",
+            feature = "face_animator",
+            feature = "voice_conversion"
+        )),
+        allow(dead_code)
+    )]
+    fn register(active_polls: &'a Mutex<ActivePolls>, handle: AbortHandle) -> Self {
+        let mut polls = active_polls.lock().expect("active polls lock poisoned");
+        polls.next_id += 1;
+        let id = polls.next_id;
+        polls.handles.insert(id, handle);
+        ActivePollGuard { active_polls, id }
+    }
+}
+
+impl Drop for ActivePollGuard<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut polls) = self.active_polls.lock() {
+            polls.handles.remove(&self.id);
+        }
+    }
+}
+
+#[cfg(feature = "voices")]
+#[derive(Clone, Debug)]
+struct VoiceCache {
+    response: VoicesResponse,
+    etag: Option<String>,
+    fetched_at: Instant,
+}
+
+#[cfg(feature = "voices")]
+impl VoiceCache {
+    fn is_fresh(&self, ttl: Option<Duration>) -> bool {
+        ttl.is_some_and(|ttl| self.fetched_at.elapsed() < ttl)
+    }
+}
+
+#[cfg(feature = "voices")]
+#[derive(Clone, Debug, Deserialize)]
+struct TtsListResponse {
+    models: Option<Vec<TtsVoice>>,
+    #[serde(default)]
+    maybe_pagination: Option<Pagination>,
+    #[serde(default)]
+    refresh_interval_millis: Option<u64>,
+}
+
+#[cfg(feature = "voices")]
+#[derive(Clone, Debug, Deserialize)]
+struct TtsModelResponse {
+    model: TtsVoice,
+}
+
+#[cfg(feature = "voices")]
+#[derive(Clone, Debug)]
+pub struct VoicesResponse {
+    pub models: Vec<TtsVoice>,
+    pub maybe_pagination: Option<Pagination>,
+    pub refresh_interval_millis: Option<u64>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Pagination {
+    #[serde(default)]
+    pub total_count: Option<u64>,
+    #[serde(default)]
+    pub maybe_next: Option<String>,
+    #[serde(default)]
+    pub maybe_previous: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct TtsVoice {
+    pub model_token: String,
+    pub tts_model_type: String,
+    pub title: String,
+    pub ietf_language_tag: String,
+    pub ietf_primary_language_subtag: String,
+    #[serde(default)]
+    pub supported_languages: Vec<LanguageTag>,
+    #[serde(default)]
+    pub user_ratings: UserRatings,
+    #[serde(default)]
+    pub maybe_model_version: Option<String>,
+    #[serde(default)]
+    pub created_at: Option<String>,
+    #[serde(default)]
+    pub updated_at: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct UserRatings {
+    #[serde(default)]
+    pub positive_count: u32,
+    #[serde(default)]
+    pub negative_count: u32,
+    #[serde(default)]
+    pub total_count: u32,
+}
+
+impl UserRatings {
+    /// Lower bound of the Wilson score interval (95% confidence) for the positive ratio, so a
+    /// voice with a few perfect ratings doesn't outrank one with thousands of mostly positive ones.
+    pub fn score(&self) -> f64 {
+        let total = f64::from(self.positive_count) + f64::from(self.negative_count);
+        if total == 0.0 {
+            return 0.0;
+        }
+        let z = 1.96;
+        let positive = f64::from(self.positive_count) / total;
+        (positive + z * z / (2.0 * total)
+            - z * ((positive * (1.0 - positive) + z * z / (4.0 * total)) / total).sqrt())
+            / (1.0 + z * z / total)
+    }
+}
+
+impl TtsVoice {
+    pub fn supports_language(&self, tag: &str) -> bool {
+        if self.supported_languages.is_empty() {
+            return self.ietf_language_tag.eq_ignore_ascii_case(tag)
+                || self.ietf_primary_language_subtag.eq_ignore_ascii_case(tag);
+        }
+        self.supported_languages
+            .iter()
+            .any(|language| language.as_str().eq_ignore_ascii_case(tag))
+    }
+
+    #[cfg(feature = "language_tags")]
+    pub fn matches_language(&self, requested: &LanguageTag) -> bool {
+        if self.supported_languages.is_empty() {
+            return LanguageTag::from(self.ietf_language_tag.as_str()).matches(requested);
+        }
+        self.supported_languages
+            .iter()
+            .any(|language| language.matches(requested))
+    }
+}
+
+/// Filters for `voices_filtered`. Language, model type and category are sent to the server,
+/// language, model type and title are also checked locally in case the server ignores them.
+#[cfg(feature = "voices")]
+#[derive(Clone, Debug, Default)]
+pub struct VoiceFilter {
+    language: Option<String>,
+    model_type: Option<String>,
+    category_token: Option<String>,
+    title_contains: Option<String>,
+}
+
+#[cfg(feature = "voices")]
+impl VoiceFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn language<S: Into<String>>(mut self, language: S) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    pub fn model_type<S: Into<String>>(mut self, model_type: S) -> Self {
+        self.model_type = Some(model_type.into());
+        self
+    }
+
+    pub fn category_token<S: Into<String>>(mut self, category_token: S) -> Self {
+        self.category_token = Some(category_token.into());
+        self
+    }
+
+    /// Case insensitive, only checked locally.
+    pub fn title_contains<S: Into<String>>(mut self, title_contains: S) -> Self {
+        self.title_contains = Some(title_contains.into().to_lowercase());
+        self
+    }
+
+    fn query_params(&self) -> Vec<(&'static str, &str)> {
+        [
+            ("language", &self.language),
+            ("tts_model_type", &self.model_type),
+            ("category_token", &self.category_token),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| value.as_deref().map(|value| (name, value)))
+        .collect()
+    }
+
+    fn matches(&self, voice: &TtsVoice) -> bool {
+        self.language
+            .as_ref()
+            .is_none_or(|language| voice.supports_language(language))
+            && self
+                .model_type
+                .as_ref()
+                .is_none_or(|model_type| &voice.tts_model_type == model_type)
+            && self
+                .title_contains
+                .as_ref()
+                .is_none_or(|title| voice.title.to_lowercase().contains(title))
+    }
+}
+
+#[cfg(feature = "leaderboard")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LeaderboardKind {
+    Tts,
+    FaceAnimation,
+}
+
+#[cfg(feature = "leaderboard")]
+#[derive(Clone, Debug, Deserialize)]
+struct LeaderboardResponse {
+    #[serde(default)]
+    tts_leaderboard: Vec<LeaderboardEntry>,
+    // face animations were called wav2lip (w2l) when the leaderboard was added
+    #[serde(default)]
+    w2l_leaderboard: Vec<LeaderboardEntry>,
+}
+
+#[cfg(feature = "leaderboard")]
+#[derive(Clone, Debug, Deserialize)]
+pub struct LeaderboardEntry {
+    pub creator_user_token: String,
+    pub username: String,
+    pub display_name: String,
+    #[serde(default)]
+    pub gravatar_hash: Option<String>,
+    pub uploaded_count: u64,
+}
+
+#[cfg(feature = "voice_conversion")]
+#[derive(Clone, Debug, Deserialize)]
+pub struct VoiceConversionModel {
+    #[serde(alias = "token")]
+    pub model_token: String,
+    pub title: String,
+    pub creator_username: String,
+    pub ietf_language_tag: String,
+    #[serde(default)]
+    pub maybe_target_sample_rate: Option<u32>,
+}
+
+#[cfg(feature = "voice_conversion")]
+#[derive(Clone, Debug, Serialize)]
+struct VoiceConversionInferencePayload {
+    uuid_idempotency_token: Uuid,
+    voice_conversion_model_token: String,
+    source_media_upload_token: String,
+}
+
+#[cfg(feature = "voice_conversion")]
+#[derive(Clone, Debug, Deserialize)]
+struct VoiceConversionInferenceResponse {
+    inference_job_token: String,
+}
+
+#[cfg(feature = "voice_conversion")]
+#[derive(Clone, Debug, Deserialize)]
+struct VoiceConversionJobResponse {
+    success: bool,
+    state: VoiceConversionJobState,
+}
+
+#[cfg(feature = "voice_conversion")]
+#[derive(Clone, Debug, Deserialize)]
+struct VoiceConversionJobState {
+    job_token: String,
+    status: VoiceConversionStatus,
+    #[serde(default)]
+    maybe_result: Option<VoiceConversionResult>,
+}
+
+#[cfg(feature = "voice_conversion")]
+#[derive(Clone, Debug, Deserialize)]
+struct VoiceConversionStatus {
+    status: JobStatus,
+}
+
+#[cfg(feature = "voice_conversion")]
+#[derive(Clone, Debug, Deserialize)]
+struct VoiceConversionResult {
+    #[serde(default)]
+    maybe_public_bucket_media_path: Option<String>,
+}
+
+#[cfg(feature = "voice_conversion")]
+impl VoiceConversionModel {
+    /// Checks that wav `audio` matches the sample rate the model expects, models that don't
+    /// publish one accept any audio.
+    pub fn validate_sample_rate(&self, audio: &[u8]) -> Result<(), Error> {
+        let Some(expected) = self.maybe_target_sample_rate else {
+            return Ok(());
+        };
+        let actual = wav_sample_rate(audio).ok_or(anyhow::anyhow!(
+            "Failed to read the sample rate, audio is not a valid wav file"
+        ))?;
+        if actual != expected {
+            return Err(Error::SampleRateMismatch { expected, actual });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "voice_conversion")]
+fn wav_sample_rate(audio: &[u8]) -> Option<u32> {
+    if audio.get(0..4)? != b"RIFF" || audio.get(8..12)? != b"WAVE" {
+        return None;
+    }
+    let mut offset = 12;
+    while let Some(chunk_header) = audio.get(offset..offset + 8) {
+        let chunk_size = u32::from_le_bytes(chunk_header[4..8].try_into().ok()?) as usize;
+        if &chunk_header[0..4] == b"fmt " {
+            let sample_rate = audio.get(offset + 12..offset + 16)?;
+            return Some(u32::from_le_bytes(sample_rate.try_into().ok()?));
+        }
+        // chunks are padded to an even size
+        offset += 8 + chunk_size + chunk_size % 2;
+    }
+    None
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Hash)]
+#[serde(transparent)]
+pub struct LanguageTag(String);
+
+impl LanguageTag {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+#[cfg(feature = "language_tags")]
+impl LanguageTag {
+    /// Parses and canonicalizes the tag, e.g. `en-us` becomes `en-US`.
+    pub fn parse(&self) -> Result<language_tags::LanguageTag, Error> {
+        language_tags::LanguageTag::parse(&self.0)
+            .ok()
+            .and_then(|tag| tag.canonicalize().ok())
+            .ok_or_else(|| Error::InvalidLanguageTag {
+                tag: self.0.clone(),
+            })
+    }
+
+    /// Whether this tag falls within `requested`, so `en-US` matches a request for `en` but
+    /// not the other way around. Invalid tags never match.
+    pub fn matches(&self, requested: &LanguageTag) -> bool {
+        match (requested.parse(), self.parse()) {
+            (Ok(range), Ok(tag)) => range.is_language_range() && range.matches(&tag),
+            _ => false,
+        }
+    }
+}
+
+impl From<&str> for LanguageTag {
+    fn from(tag: &str) -> Self {
+        LanguageTag(tag.to_string())
+    }
+}
+
+impl std::fmt::Display for LanguageTag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(feature = "model_management")]
+#[derive(Builder, Clone, Debug, Serialize)]
+pub struct CreateModelPayload {
+    #[builder(setter(into))]
+    title: String,
+    #[builder(setter(into))]
+    weights_upload_token: String,
+    #[builder(setter(into))]
+    tts_model_type: String,
+    #[builder(setter(into))]
+    ietf_language_tag: String,
+    #[builder(default)]
+    creator_set_visibility: ModelVisibility,
+    #[builder(default = "Uuid::new_v4()")]
+    uuid_idempotency_token: Uuid,
+}
+
+#[cfg(feature = "model_management")]
+#[derive(Clone, Copy, Debug, Default, Serialize)]
+#[serde(rename_all(serialize = "snake_case"))]
+pub enum ModelVisibility {
+    #[default]
+    Public,
+    Hidden,
+    Private,
+}
+
+#[cfg(feature = "model_management")]
+#[derive(Clone, Debug, Deserialize)]
+struct CreateModelResponse {
+    model_token: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct UploadFilePayload<'a> {
+    uuid_idempotency_token: Uuid,
+    file: &'a [u8],
+    source: UploadSource,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UploadSource {
+    File,
+    Url,
+    MediaUpload,
+}
+
+impl UploadSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UploadSource::File => "file",
+            UploadSource::Url => "url",
+            UploadSource::MediaUpload => "media_upload",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct UploadFileResponse {
+    pub success: bool,
+    pub upload_token: String,
+}
+
+#[cfg(feature = "face_animator")]
+#[derive(Clone, Debug, Deserialize)]
+struct FaceAnimationDimensionsResponse {
+    dimensions: Vec<String>,
+}
+
+#[cfg(feature = "face_animator")]
+#[derive(Clone, Debug, Deserialize)]
+struct MediaUploadStatusResponse {
+    media_upload: MediaUploadState,
+}
+
+#[cfg(feature = "face_animator")]
+#[derive(Clone, Debug, Deserialize)]
+pub struct MediaUploadState {
+    #[serde(alias = "upload_token")]
+    pub token: String,
+    #[serde(default)]
+    pub media_type: Option<String>,
+    #[serde(default)]
+    pub maybe_public_bucket_path: Option<String>,
+    pub created_at: String,
+}
+
+#[cfg(feature = "face_animator")]
+impl MediaUploadState {
+    fn dry_run(token: String) -> Self {
+        MediaUploadState {
+            maybe_public_bucket_path: Some(format!("/dry_run/{token}")),
+            token,
+            media_type: None,
+            created_at: String::new(),
+        }
+    }
+
+    /// Uploads only get a public path once they have been processed.
+    pub fn is_ready(&self) -> bool {
+        self.maybe_public_bucket_path.is_some()
+    }
+}
+
+#[cfg(feature = "face_animator")]
+#[derive(Builder, Clone, Debug, Serialize)]
+pub struct CreateFaceAnimationPayload {
+    #[builder(setter(custom))]
+    audio_sorce: FaceAnimationMediaSource,
+    /// Falls back to the client's `default_face_animation_dimensions` when unset.
+    #[builder(default, setter(strip_option))]
+    dimensions: Option<String>,
+    /// Skips the face enhancement pass, faster but produces a blurrier face.
+    #[builder(default)]
+    disable_face_enhancement: bool,
+    #[builder(setter(custom))]
+    image_source: FaceAnimationMediaSource,
+    /// Keeps the head still and only animates the face.
+    #[builder(default)]
+    make_still: bool,
+    /// Removes the FakeYou watermark from the result, requires a premium account.
+    #[builder(default)]
+    remove_watermark: bool,
+    #[builder(default = "Uuid::new_v4()")]
+    uuid_idempotency_token: Uuid,
+}
+
+#[cfg(feature = "face_animator")]
+impl CreateFaceAnimationPayload {
+    pub fn builder() -> CreateFaceAnimationPayloadBuilder {
+        CreateFaceAnimationPayloadBuilder::create_empty()
+    }
+
+    pub fn audio_sorce(&mut self, maybe_media_upload_token: String) {
+        self.audio_sorce = FaceAnimationMediaSource {
+            maybe_media_upload_token,
+        };
+    }
+
+    pub fn image_source(&mut self, maybe_media_upload_token: String) {
+        self.image_source = FaceAnimationMediaSource {
+            maybe_media_upload_token,
+        };
+    }
+}
+
+#[cfg(feature = "face_animator")]
+impl CreateFaceAnimationPayloadBuilder {
+    /// Sets the token the server uses to deduplicate submissions, reuse the same token when
+    /// retrying a submission so a failed request that actually landed isn't animated twice.
+    /// Defaults to a random token.
+    pub fn idempotency_token(&mut self, uuid_idempotency_token: Uuid) -> &mut Self {
+        self.uuid_idempotency_token = Some(uuid_idempotency_token);
+        self
+    }
+
+    pub fn audio_sorce(&mut self, maybe_media_upload_token: String) -> &mut Self {
+        self.audio_sorce = Some(FaceAnimationMediaSource {
+            maybe_media_upload_token,
+        });
+        self
+    }
+
+    pub fn image_source(&mut self, maybe_media_upload_token: String) -> &mut Self {
+        self.image_source = Some(FaceAnimationMediaSource {
+            maybe_media_upload_token,
+        });
+        self
+    }
+}
+
+#[cfg(feature = "face_animator")]
+#[derive(Clone, Debug, Deserialize)]
+struct ActiveSubscriptionsResponse {
+    active_subscriptions: Option<Vec<serde_json::Value>>,
+}
+
+#[cfg(feature = "face_animator")]
+#[derive(Clone, Debug, Serialize)]
+pub struct FaceAnimationMediaSource {
+    maybe_media_upload_token: String,
+}
+
+#[cfg(feature = "face_animator")]
+#[derive(Clone, Debug, Deserialize)]
+pub struct CreateFaceAnimationResponse {
+    pub success: bool,
+    pub inference_job_token: String,
+}
+
+#[cfg(feature = "face_animator")]
+#[derive(Clone, Debug, Deserialize)]
+pub struct FaceAnimationJobResponse {
+    pub success: bool,
+    pub state: FaceAnimationJobState,
+}
+
+#[cfg(feature = "face_animator")]
+#[derive(Clone, Debug, Deserialize)]
+pub struct FaceAnimationJobState {
+    pub job_token: String,
+    pub request: FaceAnimationRequest,
+    pub status: FaceAnimationStatus,
+    #[serde(default)]
+    pub maybe_result: Option<FaceAnimationResult>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[cfg(feature = "face_animator")]
+impl FaceAnimationJobResponse {
+    // a completed job for a token returned by a dry run, its video doesn't exist
+    fn dry_run(job_token: String) -> Self {
+        FaceAnimationJobResponse {
+            success: true,
+            state: FaceAnimationJobState {
+                maybe_result: Some(FaceAnimationResult {
+                    entity_type: EntityType::MediaFile,
+                    entity_token: job_token.clone(),
+                    maybe_public_bucket_media_path: Some(format!("/dry_run/{job_token}.mp4")),
+                    maybe_successfully_completed_at: None,
+                }),
+                job_token,
+                request: FaceAnimationRequest {
+                    inference_category: InferenceCategory::FaceAnimation,
+                    maybe_model_type: None,
+                    maybe_model_token: None,
+                    maybe_model_title: None,
+                    maybe_raw_inference_text: None,
+                },
+                status: FaceAnimationStatus {
+                    status: JobStatus::CompleteSuccess,
+                    maybe_extra_status_description: None,
+                    maybe_assigned_worker: None,
+                    maybe_assigned_cluster: None,
+                    maybe_first_started_at: None,
+                    attempt_count: 0,
+                    require_keepalive: false,
+                    maybe_failure_category: None,
+                },
+                created_at: String::new(),
+                updated_at: String::new(),
+            },
+        }
+    }
+}
+
+#[cfg(feature = "face_animator")]
+impl FaceAnimationJobState {
+    /// The text spoken in the animation, only present when the audio came from tts rather
+    /// than an uploaded clip.
+    pub fn raw_inference_text(&self) -> Option<&str> {
+        self.request.maybe_raw_inference_text.as_deref()
+    }
+}
+
+#[cfg(feature = "face_animator")]
+impl JobLifecycle for FaceAnimationJobState {
+    fn status(&self) -> &JobStatus {
+        &self.status.status
+    }
+
+    fn job_token(&self) -> &str {
+        &self.job_token
+    }
+}
+
+#[cfg(feature = "face_animator")]
+#[derive(Clone, Debug, Deserialize)]
+pub struct FaceAnimationRequest {
+    pub inference_category: InferenceCategory,
+    #[serde(default)]
+    pub maybe_model_type: Option<String>,
+    #[serde(default)]
+    pub maybe_model_token: Option<String>,
+    #[serde(default)]
+    pub maybe_model_title: Option<String>,
+    #[serde(default)]
+    pub maybe_raw_inference_text: Option<String>,
+}
+
+#[cfg(feature = "face_animator")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InferenceCategory {
+    FaceAnimation,
+    TextToSpeech,
+    VoiceConversion,
+    Other(String),
+}
+
+#[cfg(feature = "face_animator")]
+impl<'de> Deserialize<'de> for InferenceCategory {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let inference_category = String::deserialize(deserializer)?;
+        Ok(match inference_category.as_str() {
+            "face_animation" | "lipsync_animation" => InferenceCategory::FaceAnimation,
+            "tts" | "text_to_speech" => InferenceCategory::TextToSpeech,
+            "voice_conversion" => InferenceCategory::VoiceConversion,
+            _ => InferenceCategory::Other(inference_category),
+        })
+    }
+}
+
+#[cfg(feature = "face_animator")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EntityType {
+    MediaFile,
+    TtsResult,
+    VoiceConversionResult,
+    Other(String),
+}
+
+#[cfg(feature = "face_animator")]
+impl<'de> Deserialize<'de> for EntityType {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let entity_type = String::deserialize(deserializer)?;
+        Ok(match entity_type.as_str() {
+            "media_file" => EntityType::MediaFile,
+            "tts_result" => EntityType::TtsResult,
+            "voice_conversion_result" => EntityType::VoiceConversionResult,
+            _ => EntityType::Other(entity_type),
+        })
+    }
 }
 
-#[derive(Clone, Debug, Deserialize)]
-#[serde(rename_all(deserialize = "snake_case"))]
-pub enum JobStatus {
-    AttemptFailed,
-    CompleteFailure,
-    CompleteSuccess,
-    Dead,
-    Pending,
-    Started,
-}
+#[cfg(feature = "face_animator")]
+#[derive(Clone, Debug, Deserialize)]
+pub struct FaceAnimationStatus {
+    pub status: JobStatus,
+    #[serde(default)]
+    pub maybe_extra_status_description: Option<String>,
+    #[serde(default)]
+    pub maybe_assigned_worker: Option<String>,
+    #[serde(default)]
+    pub maybe_assigned_cluster: Option<String>,
+    #[serde(default)]
+    pub maybe_first_started_at: Option<String>,
+    pub attempt_count: u32,
+    pub require_keepalive: bool,
+    #[serde(default)]
+    pub maybe_failure_category: Option<String>,
+}
+
+#[cfg(feature = "face_animator")]
+#[derive(Clone, Debug, Deserialize)]
+pub struct FaceAnimationResult {
+    pub entity_type: EntityType,
+    pub entity_token: String,
+    #[serde(default)]
+    pub maybe_public_bucket_media_path: Option<String>,
+    #[serde(default)]
+    pub maybe_successfully_completed_at: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{MockResponse, MockServer};
+
+    #[tokio::test]
+    async fn download_failure_keeps_status_code() {
+        let server = MockServer::start();
+        server.mock(
+            "GET",
+            "/vocodes-public/audio/result.wav",
+            MockResponse::status(503),
+        );
+        let error = server
+            .client()
+            .download_audio("/audio/result.wav")
+            .await
+            .unwrap_err();
+        assert!(matches!(error, Error::DownloadFailed { .. }), "{error:?}");
+        assert_eq!(error.status_code(), Some(503));
+    }
+
+    #[cfg(feature = "tts")]
+    #[tokio::test]
+    async fn job_fetch_failure_keeps_status_code() {
+        let server = MockServer::start();
+        server.mock("GET", "/tts/job/JTINF:1", MockResponse::status(503));
+        let error = server.client().tts_job_raw("JTINF:1").await.unwrap_err();
+        assert!(matches!(error, Error::Http { .. }), "{error:?}");
+        assert_eq!(error.status_code(), Some(503));
+    }
+
+    #[cfg(feature = "face_animator")]
+    fn face_animation_job(status: &str) -> serde_json::Value {
+        json!({
+            "success": true,
+            "state": {
+                "job_token": "JINF:1",
+                "request": {"inference_category": "lipsync_animation"},
+                "status": {
+                    "status": status,
+                    "attempt_count": 0,
+                    "require_keepalive": false
+                },
+                "maybe_result": null,
+                "created_at": "2023-01-01T00:00:00Z",
+                "updated_at": "2023-01-01T00:00:00Z"
+            }
+        })
+    }
+
+    #[cfg(feature = "face_animator")]
+    fn mock_uploads(server: &MockServer, delay: Duration) {
+        for endpoint in ["upload_audio", "upload_image"] {
+            server.mock(
+                "POST",
+                &format!("/media_uploads/{endpoint}"),
+                MockResponse::json(json!({"success": true, "upload_token": endpoint})).delay(delay),
+            );
+        }
+    }
+
+    #[cfg(feature = "face_animator")]
+    #[tokio::test]
+    async fn animate_face_uploads_concurrently() {
+        let server = MockServer::start();
+        mock_uploads(&server, Duration::from_millis(300));
+        server
+            .mock(
+                "POST",
+                "/animation/face_animation/create",
+                MockResponse::json(json!({"success": true, "inference_job_token": "JINF:1"})),
+            )
+            .mock(
+                "GET",
+                "/model_inference/job_status/JINF:1",
+                MockResponse::json(face_animation_job("complete_success")),
+            );
+        server
+            .client()
+            .animate_face(b"audio", b"image", CreateFaceAnimationPayload::builder())
+            .await
+            .unwrap();
+        assert_eq!(server.max_in_flight(), 2);
+        let create = server.received("POST", "/animation/face_animation/create");
+        assert_eq!(
+            create[0].json()["audio_sorce"]["maybe_media_upload_token"],
+            "upload_audio"
+        );
+        assert_eq!(
+            create[0].json()["image_source"]["maybe_media_upload_token"],
+            "upload_image"
+        );
+    }
+
+    #[cfg(feature = "face_animator")]
+    #[tokio::test]
+    async fn animate_face_creates_no_job_when_an_upload_fails() {
+        let server = MockServer::start();
+        server
+            .mock(
+                "POST",
+                "/media_uploads/upload_audio",
+                MockResponse::json(json!({"success": true, "upload_token": "upload_audio"})),
+            )
+            .mock(
+                "POST",
+                "/media_uploads/upload_image",
+                MockResponse::status(500),
+            );
+        let error = server
+            .client()
+            .animate_face(b"audio", b"image", CreateFaceAnimationPayload::builder())
+            .await
+            .unwrap_err();
+        assert_eq!(error.status_code(), Some(500));
+        assert!(server
+            .received("POST", "/animation/face_animation/create")
+            .is_empty());
+    }
+
+    // records the fields of every span created while it is the default subscriber
+    #[cfg(feature = "tracing")]
+    #[derive(Clone, Default)]
+    struct SpanRecorder(Arc<Mutex<Vec<(String, String)>>>);
+
+    #[cfg(feature = "tracing")]
+    impl SpanRecorder {
+        fn field(&self, name: &str) -> Option<String> {
+            self.0
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|(field, _)| field == name)
+                .map(|(_, value)| value.clone())
+        }
+    }
+
+    #[cfg(feature = "tracing")]
+    impl tracing::field::Visit for SpanRecorder {
+        fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+            let field = field.name().to_string();
+            self.0.lock().unwrap().push((field, value.to_string()));
+        }
+
+        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+            let field = field.name().to_string();
+            self.0.lock().unwrap().push((field, format!("{value:?}")));
+        }
+    }
+
+    #[cfg(feature = "tracing")]
+    impl tracing::Subscriber for SpanRecorder {
+        fn enabled(&self, _: &tracing::Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+            span.record(&mut self.clone());
+            tracing::span::Id::from_u64(1)
+        }
+
+        fn record(&self, _: &tracing::span::Id, _: &tracing::span::Record<'_>) {}
+
+        fn record_follows_from(&self, _: &tracing::span::Id, _: &tracing::span::Id) {}
+
+        fn event(&self, _: &tracing::Event<'_>) {}
+
+        fn enter(&self, _: &tracing::span::Id) {}
+
+        fn exit(&self, _: &tracing::span::Id) {}
+    }
+
+    #[cfg(all(feature = "tracing", feature = "tts"))]
+    async fn traced_inference_text(client: Client) -> Option<String> {
+        let recorder = SpanRecorder::default();
+        let _guard = tracing::subscriber::set_default(recorder.clone());
+        client
+            .tts_inference("TM:1", "my secret text")
+            .await
+            .unwrap();
+        recorder.field("inference_text")
+    }
+
+    #[cfg(all(feature = "tracing", feature = "tts"))]
+    #[tokio::test]
+    async fn inference_text_is_redacted_in_spans_by_default() {
+        let server = MockServer::start();
+        server.mock(
+            "POST",
+            "/tts/inference",
+            MockResponse::json(json!({"success": true, "inference_job_token": "JTINF:1"})),
+        );
+        let inference_text = traced_inference_text(server.client()).await;
+        assert_eq!(inference_text.as_deref(), Some(REDACTED));
+    }
+
+    #[cfg(all(feature = "tracing", feature = "tts"))]
+    #[tokio::test]
+    async fn inference_text_is_logged_when_redaction_is_disabled() {
+        let server = MockServer::start();
+        server.mock(
+            "POST",
+            "/tts/inference",
+            MockResponse::json(json!({"success": true, "inference_job_token": "JTINF:1"})),
+        );
+        let client = server
+            .client_builder()
+            .redact_inference_text(false)
+            .build()
+            .unwrap();
+        let inference_text = traced_inference_text(client).await;
+        assert_eq!(inference_text.as_deref(), Some("my secret text"));
+    }
+
+    #[cfg(feature = "tracing")]
+    #[tokio::test]
+    async fn password_is_always_redacted_in_spans() {
+        let server = MockServer::start();
+        server.mock(
+            "POST",
+            "/login",
+            MockResponse::json(json!({"success": true})),
+        );
+        let recorder = SpanRecorder::default();
+        let _guard = tracing::subscriber::set_default(recorder.clone());
+        server
+            .client_builder()
+            .redact_inference_text(false)
+            .login("user", "hunter2")
+            .await
+            .unwrap();
+        assert_eq!(recorder.field("password").as_deref(), Some(REDACTED));
+        assert_eq!(recorder.field("username_or_email").as_deref(), Some("user"));
+    }
+
+    #[tokio::test]
+    async fn ping_succeeds_with_a_valid_session() {
+        let server = MockServer::start();
+        server.mock(
+            "GET",
+            "/v1/session",
+            MockResponse::json(json!({"logged_in": true})),
+        );
+        server.client().ping().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn ping_fails_with_a_server_error() {
+        let server = MockServer::start();
+        server.mock("GET", "/v1/session", MockResponse::status(503));
+        let error = server.client().ping().await.unwrap_err();
+        assert_eq!(error.status_code(), Some(503));
+        assert!(error.is_transient());
+    }
+
+    #[test]
+    fn request_file_url_prefixes_relative_paths_once() {
+        let client = Client::builder().build().unwrap();
+        let expected = format!("{FILE_STORAGE_BASE_URL}/media/a/b.wav");
+        for path in [
+            "/media/a/b.wav",
+            "media/a/b.wav",
+            "/vocodes-public/media/a/b.wav",
+            "vocodes-public/media/a/b.wav",
+            expected.as_str(),
+        ] {
+            assert_eq!(client.request_file_url(path), expected, "{path}");
+        }
+    }
+
+    #[test]
+    fn request_file_url_keeps_paths_that_only_start_like_the_bucket() {
+        let client = Client::builder().build().unwrap();
+        assert_eq!(
+            client.request_file_url("/vocodes-public-old/b.wav"),
+            format!("{FILE_STORAGE_BASE_URL}/vocodes-public-old/b.wav")
+        );
+    }
+
+    fn tts_voice(model_token: &str) -> serde_json::Value {
+        json!({
+            "model_token": model_token,
+            "tts_model_type": "tacotron2",
+            "title": "Voice",
+            "ietf_language_tag": "en-US",
+            "ietf_primary_language_subtag": "en"
+        })
+    }
+
+    #[test]
+    fn multilingual_voice_supports_every_listed_language() {
+        let mut voice = tts_voice("TM:1");
+        voice["supported_languages"] = json!(["en-US", "es-ES"]);
+        let voice = serde_json::from_value::<TtsVoice>(voice).unwrap();
+        assert_eq!(
+            voice.supported_languages,
+            vec![LanguageTag::from("en-US"), LanguageTag::from("es-ES")]
+        );
+        assert!(voice.supports_language("es-es"));
+        assert!(voice.supports_language("en-US"));
+        assert!(!voice.supports_language("fr-FR"));
+    }
+
+    #[test]
+    fn single_language_voice_falls_back_to_its_tags() {
+        let voice = serde_json::from_value::<TtsVoice>(tts_voice("TM:1")).unwrap();
+        assert!(voice.supported_languages.is_empty());
+        assert!(voice.supports_language("en-US"));
+        assert!(voice.supports_language("en"));
+        assert!(!voice.supports_language("es"));
+    }
+
+    #[cfg(feature = "voices")]
+    #[tokio::test]
+    async fn my_models_lists_the_session_users_models() {
+        let server = MockServer::start();
+        server
+            .mock(
+                "GET",
+                "/v1/session",
+                MockResponse::json(json!({"logged_in": true, "user": {"username": "creator"}})),
+            )
+            .mock(
+                "GET",
+                "/user/creator/tts_models",
+                MockResponse::json(json!({"tts_models": [tts_voice("TM:1"), tts_voice("TM:2")]})),
+            );
+        let models = server.client().my_models().await.unwrap();
+        let tokens = models
+            .iter()
+            .map(|model| model.model_token.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(tokens, ["TM:1", "TM:2"]);
+    }
+
+    #[cfg(feature = "voices")]
+    #[tokio::test]
+    async fn my_models_maps_401_to_authentication_error() {
+        let server = MockServer::start();
+        server
+            .mock(
+                "GET",
+                "/v1/session",
+                MockResponse::json(json!({"logged_in": true, "user": {"username": "creator"}})),
+            )
+            .mock("GET", "/user/creator/tts_models", MockResponse::status(401));
+        let error = server.client().my_models().await.unwrap_err();
+        assert!(matches!(error, Error::AuthenticationError), "{error:?}");
+    }
+
+    #[cfg(feature = "voices")]
+    #[tokio::test]
+    async fn my_models_requires_a_logged_in_session() {
+        let server = MockServer::start();
+        server.mock(
+            "GET",
+            "/v1/session",
+            MockResponse::json(json!({"logged_in": false, "user": null})),
+        );
+        let error = server.client().my_models().await.unwrap_err();
+        assert!(matches!(error, Error::AuthenticationError), "{error:?}");
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[cfg(feature = "tts")]
+    fn tts_job(job_token: &str, status: &str) -> serde_json::Value {
+        json!({
+            "success": true,
+            "state": {
+                "job_token": job_token,
+                "status": status,
+                "maybe_public_bucket_wav_audio_path": "/audio/result.wav"
+            }
+        })
+    }
+
+    #[cfg(feature = "tts")]
+    fn mock_tts_inference(server: &MockServer) {
+        server.mock(
+            "POST",
+            "/tts/inference",
+            MockResponse::json(json!({"success": true, "inference_job_token": "JTINF:1"})),
+        );
+    }
+
+    #[cfg(feature = "tts")]
+    #[tokio::test]
+    async fn synthesize_deadline_trips_during_polling() {
+        let server = MockServer::start();
+        mock_tts_inference(&server);
+        server.mock(
+            "GET",
+            "/tts/job/JTINF:1",
+            MockResponse::json(tts_job("JTINF:1", "started")),
+        );
+        let client = server
+            .client_builder()
+            .sleep_fn(tokio::time::sleep)
+            .tts_poll_config(PollConfig::new(Duration::from_millis(20)))
+            .build()
+            .unwrap();
+        let deadline = std::time::Instant::now() + Duration::from_millis(300);
+        let error = client
+            .synthesize_deadline("TM:1", "Hello", deadline)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&error, Error::Deadline { job_token: Some(job_token) } if job_token == "JTINF:1"),
+            "{error:?}"
+        );
+        assert!(server.received("GET", "/tts/job/JTINF:1").len() > 1);
+    }
+
+    #[tokio::test]
+    async fn custom_user_agent_is_sent_with_the_crate_identifier() {
+        let server = MockServer::start();
+        server.mock(
+            "GET",
+            "/v1/session",
+            MockResponse::json(json!({"logged_in": true})),
+        );
+        server
+            .client_builder()
+            .user_agent("my-app/1.0")
+            .build()
+            .unwrap()
+            .ping()
+            .await
+            .unwrap();
+        server
+            .client_builder()
+            .user_agent("my-app/1.0")
+            .append_crate_user_agent(false)
+            .build()
+            .unwrap()
+            .ping()
+            .await
+            .unwrap();
+        let user_agents = server
+            .received("GET", "/v1/session")
+            .iter()
+            .map(|request| request.header("user-agent").unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            user_agents,
+            [
+                format!("my-app/1.0 chatterverse-fakeyou-client@{CARGO_PACKAGE_VERSION}"),
+                "my-app/1.0".to_string()
+            ]
+        );
+    }
+
+    #[cfg(feature = "tts")]
+    #[test]
+    fn tts_job_state_without_worker_or_cluster_deserializes() {
+        let response =
+            serde_json::from_value::<TtsJobResponse>(tts_job("JTINF:1", "pending")).unwrap();
+        assert_eq!(response.state.maybe_assigned_worker, None);
+        assert_eq!(response.state.maybe_assigned_cluster, None);
+
+        let mut job = tts_job("JTINF:1", "started");
+        job["state"]["maybe_assigned_worker"] = json!("worker-1");
+        job["state"]["maybe_assigned_cluster"] = json!("cluster-1");
+        let response = serde_json::from_value::<TtsJobResponse>(job).unwrap();
+        assert_eq!(
+            response.state.maybe_assigned_worker.as_deref(),
+            Some("worker-1")
+        );
+        assert_eq!(
+            response.state.maybe_assigned_cluster.as_deref(),
+            Some("cluster-1")
+        );
+    }
+
+    #[cfg(feature = "face_animator")]
+    #[test]
+    fn face_animation_status_without_worker_or_cluster_deserializes() {
+        let response =
+            serde_json::from_value::<FaceAnimationJobResponse>(face_animation_job("pending"))
+                .unwrap();
+        assert_eq!(response.state.status.maybe_assigned_worker, None);
+        assert_eq!(response.state.status.maybe_assigned_cluster, None);
+    }
+
+    #[cfg(any(feature = "tts", feature = "face_animator"))]
+    fn error_envelope() -> MockResponse {
+        MockResponse::json(json!({
+            "success": false,
+            "error_type": "job_not_found",
+            "error_message": "job not found"
+        }))
+    }
+
+    #[cfg(feature = "tts")]
+    #[tokio::test]
+    async fn tts_poll_returns_the_error_envelope() {
+        let server = MockServer::start();
+        server.mock("GET", "/tts/job/JTINF:1", error_envelope());
+        let error = server.client().poll_tts_job("JTINF:1").await.unwrap_err();
+        assert!(
+            matches!(&error, Error::ApiError { error_type: Some(error_type), message }
+                if error_type == "job_not_found" && message == "job not found"),
+            "{error:?}"
+        );
+    }
+
+    #[cfg(feature = "face_animator")]
+    #[tokio::test]
+    async fn face_animation_poll_returns_the_error_envelope() {
+        let server = MockServer::start();
+        server.mock(
+            "GET",
+            "/model_inference/job_status/JINF:1",
+            error_envelope(),
+        );
+        let error = server
+            .client()
+            .poll_face_animation_job("JINF:1")
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&error, Error::ApiError { message, .. } if message == "job not found"),
+            "{error:?}"
+        );
+    }
+
+    #[cfg(any(feature = "tts", feature = "face_animator"))]
+    fn custom_header_options() -> RequestOptions {
+        RequestOptions::new().header(
+            reqwest::header::HeaderName::from_static("x-custom"),
+            reqwest::header::HeaderValue::from_static("custom-value"),
+        )
+    }
+
+    #[cfg(feature = "tts")]
+    #[tokio::test]
+    async fn tts_inference_sends_request_option_headers() {
+        let server = MockServer::start();
+        mock_tts_inference(&server);
+        server
+            .client()
+            .tts_inference_with(
+                "TM:1",
+                "Hello",
+                TtsInferenceOptions::default(),
+                custom_header_options(),
+            )
+            .await
+            .unwrap();
+        let request = &server.received("POST", "/tts/inference")[0];
+        assert_eq!(request.header("x-custom"), Some("custom-value"));
+    }
+
+    #[cfg(feature = "face_animator")]
+    #[tokio::test]
+    async fn uploads_and_animations_send_request_option_headers() {
+        let server = MockServer::start();
+        server
+            .mock(
+                "POST",
+                "/media_uploads/upload_audio",
+                MockResponse::json(json!({"success": true, "upload_token": "MU:1"})),
+            )
+            .mock(
+                "POST",
+                "/animation/face_animation/create",
+                MockResponse::json(json!({"success": true, "inference_job_token": "JINF:1"})),
+            );
+        let client = server.client();
+        client
+            .upload_audio_with(b"audio", custom_header_options())
+            .await
+            .unwrap();
+        let payload = CreateFaceAnimationPayload::builder()
+            .audio_sorce("MU:1".to_string())
+            .image_source("MU:2".to_string())
+            .build()
+            .unwrap();
+        client
+            .create_facial_animation_with(payload, custom_header_options())
+            .await
+            .unwrap();
+        for request in server.requests() {
+            assert_eq!(
+                request.header("x-custom"),
+                Some("custom-value"),
+                "{}",
+                request.path
+            );
+        }
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[cfg(feature = "voice_conversion")]
+    fn voice_conversion_model(token: &str) -> serde_json::Value {
+        json!({
+            "token": token,
+            "title": "Converted",
+            "creator_username": "creator",
+            "ietf_language_tag": "en-US"
+        })
+    }
+
+    #[cfg(feature = "voice_conversion")]
+    #[tokio::test]
+    async fn voice_conversion_models_deserialize_the_v2v_list() {
+        let server = MockServer::start();
+        server.mock(
+            "GET",
+            "/v1/voice_conversion/model_list",
+            MockResponse::json(json!({
+                "success": true,
+                "models": [
+                    voice_conversion_model("VCM:1"),
+                    {
+                        "model_token": "VCM:2",
+                        "title": "Other",
+                        "creator_username": "someone",
+                        "ietf_language_tag": "es-ES",
+                        "maybe_target_sample_rate": 16000
+                    }
+                ]
+            })),
+        );
+        let models = server.client().voice_conversion_models().await.unwrap();
+        assert_eq!(models.len(), 2);
+        assert_eq!(models[0].model_token, "VCM:1");
+        assert_eq!(models[0].creator_username, "creator");
+        assert_eq!(models[0].maybe_target_sample_rate, None);
+        assert_eq!(models[1].model_token, "VCM:2");
+        assert_eq!(models[1].ietf_language_tag, "es-ES");
+        assert_eq!(models[1].maybe_target_sample_rate, Some(16000));
+    }
+
+    #[cfg(feature = "tts")]
+    #[tokio::test]
+    async fn blank_or_too_long_inference_text_is_rejected_before_sending() {
+        let server = MockServer::start();
+        mock_tts_inference(&server);
+        let client = server
+            .client_builder()
+            .max_inference_text_len(Some(5))
+            .build()
+            .unwrap();
+        for inference_text in ["", " \n\t "] {
+            let error = client
+                .tts_inference("TM:1", inference_text)
+                .await
+                .unwrap_err();
+            assert!(matches!(error, Error::EmptyInput), "{error:?}");
+        }
+        let error = client.synthesize("TM:1", "Hello!").await.unwrap_err();
+        assert!(
+            matches!(error, Error::InputTooLong { len: 6, max: 5 }),
+            "{error:?}"
+        );
+        assert!(server.requests().is_empty());
+        client.tts_inference("TM:1", "Hello").await.unwrap();
+    }
+
+    #[cfg(feature = "voices")]
+    #[tokio::test]
+    async fn voices_parse_the_model_list() {
+        let server = MockServer::start();
+        server.mock(
+            "GET",
+            "/tts/list",
+            MockResponse::json(json!({
+                "success": true,
+                "models": [tts_voice("TM:1"), tts_voice("TM:2")]
+            })),
+        );
+        let voices = server.client().voices().await.unwrap();
+        let tokens = voices
+            .iter()
+            .map(|voice| voice.model_token.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(tokens, ["TM:1", "TM:2"]);
+    }
+
+    #[cfg(feature = "voices")]
+    #[tokio::test]
+    async fn voices_without_models_fail() {
+        let server = MockServer::start();
+        server.mock(
+            "GET",
+            "/tts/list",
+            MockResponse::json(json!({"success": true})),
+        );
+        let error = server.client().voices().await.unwrap_err();
+        assert!(
+            error.to_string().contains("missing 'models' property"),
+            "{error:?}"
+        );
+    }
+
+    #[cfg(feature = "model_management")]
+    fn create_model_payload() -> CreateModelPayload {
+        CreateModelPayloadBuilder::default()
+            .title("My voice")
+            .weights_upload_token("MU:1")
+            .tts_model_type("tacotron2")
+            .ietf_language_tag("en-US")
+            .creator_set_visibility(ModelVisibility::Hidden)
+            .build()
+            .unwrap()
+    }
+
+    #[cfg(feature = "model_management")]
+    #[tokio::test]
+    async fn create_tts_model_returns_the_new_model_token() {
+        let server = MockServer::start();
+        server.mock(
+            "POST",
+            "/v1/tts/model/create",
+            MockResponse::json(json!({"success": true, "model_token": "TM:new"})),
+        );
+        let model_token = server
+            .client()
+            .create_tts_model(create_model_payload())
+            .await
+            .unwrap();
+        assert_eq!(model_token, "TM:new");
+        let request = server.received("POST", "/v1/tts/model/create")[0].json();
+        assert_eq!(request["title"], "My voice");
+        assert_eq!(request["weights_upload_token"], "MU:1");
+        assert_eq!(request["tts_model_type"], "tacotron2");
+        assert_eq!(request["ietf_language_tag"], "en-US");
+        assert_eq!(request["creator_set_visibility"], "hidden");
+        assert!(request["uuid_idempotency_token"].is_string());
+    }
+
+    #[cfg(feature = "tts")]
+    #[tokio::test]
+    async fn delete_tts_result_succeeds_or_maps_404_to_not_found() {
+        let server = MockServer::start();
+        server
+            .mock(
+                "POST",
+                "/tts/result/TR:1/delete",
+                MockResponse::json(json!({"success": true})),
+            )
+            .mock("POST", "/tts/result/TR:2/delete", MockResponse::status(404));
+        let client = server.client();
+        client.delete_tts_result("TR:1").await.unwrap();
+        let request = &server.received("POST", "/tts/result/TR:1/delete")[0];
+        assert_eq!(request.json(), json!({"set_delete": true, "as_mod": false}));
+        let error = client.delete_tts_result("TR:2").await.unwrap_err();
+        assert!(matches!(error, Error::NotFound), "{error:?}");
+    }
+
+    #[cfg(feature = "tts")]
+    #[tokio::test]
+    async fn inference_response_headers_are_captured() {
+        let server = MockServer::start();
+        server.mock(
+            "POST",
+            "/tts/inference",
+            MockResponse::json(json!({"success": true, "inference_job_token": "JTINF:1"}))
+                .header("x-request-id", "req-1")
+                .header("cf-ray", "ray-1")
+                .header("x-ratelimit-limit", "60")
+                .header("x-ratelimit-remaining", "59")
+                .header("x-ratelimit-reset", "30"),
+        );
+        let client = server.client();
+        assert!(client.last_response_headers().is_none());
+        client.tts_inference("TM:1", "Hello").await.unwrap();
+        let headers = client.last_response_headers().unwrap();
+        assert_eq!(headers.request_id.as_deref(), Some("req-1"));
+        assert_eq!(headers.cf_ray.as_deref(), Some("ray-1"));
+        assert_eq!(headers.rate_limit_limit, Some(60));
+        assert_eq!(headers.rate_limit_remaining, Some(59));
+        assert_eq!(headers.rate_limit_reset, Some(30));
+    }
+
+    #[cfg(feature = "tts")]
+    async fn failed_job_is_retryable(status: &str, attempt_count: u32) -> bool {
+        let server = MockServer::start();
+        let mut job = tts_job("JTINF:1", status);
+        job["state"]["attempt_count"] = json!(attempt_count);
+        server.mock("GET", "/tts/job/JTINF:1", MockResponse::json(job));
+        match server.client().poll_tts_job("JTINF:1").await {
+            Err(Error::TtsJobFailed { retryable, .. }) => retryable,
+            result => panic!("unexpected poll result {result:?}"),
+        }
+    }
+
+    #[cfg(feature = "tts")]
+    #[tokio::test]
+    async fn dead_job_with_attempts_left_is_retryable() {
+        assert!(failed_job_is_retryable("dead", 1).await);
+    }
+
+    #[cfg(feature = "tts")]
+    #[tokio::test]
+    async fn exhausted_or_failed_jobs_are_permanent() {
+        assert!(!failed_job_is_retryable("dead", MAX_JOB_ATTEMPTS).await);
+        assert!(!failed_job_is_retryable("complete_failure", 1).await);
+    }
+
+    #[cfg(feature = "face_animator")]
+    #[tokio::test]
+    async fn face_animation_idempotency_token_is_serialized() {
+        let server = MockServer::start();
+        server.mock(
+            "POST",
+            "/animation/face_animation/create",
+            MockResponse::json(json!({"success": true, "inference_job_token": "JINF:1"})),
+        );
+        let idempotency_token = Uuid::new_v4();
+        let payload = CreateFaceAnimationPayload::builder()
+            .audio_sorce("MU:1".to_string())
+            .image_source("MU:2".to_string())
+            .idempotency_token(idempotency_token)
+            .build()
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(&payload).unwrap()["uuid_idempotency_token"],
+            idempotency_token.to_string()
+        );
+        server
+            .client()
+            .create_facial_animation(payload)
+            .await
+            .unwrap();
+        let request = &server.received("POST", "/animation/face_animation/create")[0];
+        assert_eq!(
+            request.json()["uuid_idempotency_token"],
+            idempotency_token.to_string()
+        );
+    }
+
+    #[cfg(feature = "tts")]
+    #[tokio::test]
+    async fn synthesize_batch_partition_indexes_failures() {
+        let server = MockServer::start();
+        for (index, status) in ["complete_success", "complete_failure", "complete_success"]
+            .into_iter()
+            .enumerate()
+        {
+            let job_token = format!("JTINF:{index}");
+            server
+                .mock(
+                    "POST",
+                    "/tts/inference",
+                    MockResponse::json(json!({"success": true, "inference_job_token": job_token})),
+                )
+                .mock(
+                    "GET",
+                    &format!("/tts/job/{job_token}"),
+                    MockResponse::json(tts_job(&job_token, status)),
+                );
+        }
+        // the last inference response repeats, so the fourth text gets a fresh job
+        server.mock(
+            "POST",
+            "/tts/inference",
+            MockResponse::json(json!({"success": false, "error_reason": "rejected"})),
+        );
+        let texts = ["a", "b", "c", "d"].map(String::from).to_vec();
+        let (successes, failures) = server
+            .client()
+            .synthesize_batch_partition("TM:1", texts, 1)
+            .await;
+        let succeeded = successes
+            .iter()
+            .map(|result| result.job_token.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(succeeded, ["JTINF:0", "JTINF:2"]);
+        let failed = failures.iter().map(|(index, _)| *index).collect::<Vec<_>>();
+        assert_eq!(failed, [1, 3]);
+        assert!(matches!(failures[0].1, Error::TtsJobFailed { .. }));
+        assert!(matches!(failures[1].1, Error::ApiError { .. }));
+    }
+
+    #[cfg(feature = "voices")]
+    fn mock_voice_list(server: &MockServer) {
+        server
+            .mock(
+                "GET",
+                "/tts/list",
+                MockResponse::json(json!({"success": true, "models": [tts_voice("TM:1")]}))
+                    .header("etag", "\"v1\""),
+            )
+            .mock("GET", "/tts/list", MockResponse::status(304));
+    }
+
+    #[cfg(feature = "voices")]
+    #[tokio::test]
+    async fn not_modified_voices_are_served_from_the_cache() {
+        let server = MockServer::start();
+        mock_voice_list(&server);
+        let client = server
+            .client_builder()
+            .voices_cache_ttl(Some(Duration::ZERO))
+            .build()
+            .unwrap();
+        client.voices().await.unwrap();
+        let voices = client.voices().await.unwrap();
+        assert_eq!(voices.len(), 1);
+        assert_eq!(voices[0].model_token, "TM:1");
+        let requests = server.received("GET", "/tts/list");
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].header("if-none-match"), None);
+        assert_eq!(requests[1].header("if-none-match"), Some("\"v1\""));
+    }
+
+    #[cfg(feature = "voices")]
+    #[tokio::test]
+    async fn fresh_voices_are_served_without_a_request() {
+        let server = MockServer::start();
+        mock_voice_list(&server);
+        let client = server
+            .client_builder()
+            .voices_cache_ttl(Some(Duration::from_secs(60)))
+            .build()
+            .unwrap();
+        client.voices().await.unwrap();
+        assert_eq!(client.voices().await.unwrap().len(), 1);
+        assert_eq!(server.received("GET", "/tts/list").len(), 1);
+    }
+
+    #[test]
+    fn job_status_helpers_classify_every_status() {
+        let terminal = JobStatus::all()
+            .iter()
+            .filter(|status| status.is_terminal())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            terminal,
+            [
+                &JobStatus::CompleteFailure,
+                &JobStatus::CompleteSuccess,
+                &JobStatus::Dead
+            ]
+        );
+        let failures = JobStatus::all()
+            .iter()
+            .filter(|status| status.is_failure())
+            .collect::<Vec<_>>();
+        assert_eq!(failures, [&JobStatus::CompleteFailure, &JobStatus::Dead]);
+        assert!(JobStatus::all()
+            .iter()
+            .all(|status| !status.is_failure() || status.is_terminal()));
+        assert_eq!(JobStatus::all().len(), 6);
+    }
+
+    #[cfg(feature = "tts")]
+    #[tokio::test]
+    async fn tts_poll_continues_past_failed_attempts() {
+        let server = MockServer::start();
+        for status in ["pending", "attempt_failed", "complete_success"] {
+            server.mock(
+                "GET",
+                "/tts/job/JTINF:1",
+                MockResponse::json(tts_job("JTINF:1", status)),
+            );
+        }
+        let response = server.client().poll_tts_job("JTINF:1").await.unwrap();
+        assert_eq!(response.state.status, JobStatus::CompleteSuccess);
+        assert_eq!(server.received("GET", "/tts/job/JTINF:1").len(), 3);
+    }
+
+    #[cfg(feature = "face_animator")]
+    #[tokio::test]
+    async fn face_animation_poll_continues_past_failed_attempts() {
+        let server = MockServer::start();
+        for status in ["started", "attempt_failed", "complete_success"] {
+            server.mock(
+                "GET",
+                "/model_inference/job_status/JINF:1",
+                MockResponse::json(face_animation_job(status)),
+            );
+        }
+        let response = server
+            .client()
+            .poll_face_animation_job("JINF:1")
+            .await
+            .unwrap();
+        assert_eq!(response.state.status.status, JobStatus::CompleteSuccess);
+        assert_eq!(
+            server
+                .received("GET", "/model_inference/job_status/JINF:1")
+                .len(),
+            3
+        );
+    }
+
+    #[cfg(feature = "tts")]
+    #[tokio::test]
+    async fn resume_tts_job_polls_an_existing_job_to_its_result() {
+        let server = MockServer::start();
+        server
+            .mock(
+                "GET",
+                "/tts/job/JTINF:1",
+                MockResponse::json(tts_job("JTINF:1", "started")),
+            )
+            .mock(
+                "GET",
+                "/tts/job/JTINF:1",
+                MockResponse::json(tts_job("JTINF:1", "complete_success")),
+            );
+        let result = server.client().resume_tts_job("JTINF:1").await.unwrap();
+        assert_eq!(result.job_token, "JTINF:1");
+        assert_eq!(
+            result.audio_url,
+            format!("{}/audio/result.wav", server.storage_uri())
+        );
+        assert_eq!(result.maybe_inference_text, None);
+        assert!(server.received("POST", "/tts/inference").is_empty());
+    }
+
+    #[cfg(feature = "face_animator")]
+    #[test]
+    fn face_animation_payload_defaults_its_flags() {
+        let payload = CreateFaceAnimationPayload::builder()
+            .audio_sorce("MU:1".to_string())
+            .image_source("MU:2".to_string())
+            .build()
+            .unwrap();
+        let payload = serde_json::to_value(payload).unwrap();
+        assert_eq!(payload["disable_face_enhancement"], false);
+        assert_eq!(payload["make_still"], false);
+        assert_eq!(payload["remove_watermark"], false);
+        assert_eq!(payload["dimensions"], serde_json::Value::Null);
+        assert_eq!(payload["audio_sorce"]["maybe_media_upload_token"], "MU:1");
+        assert_eq!(payload["image_source"]["maybe_media_upload_token"], "MU:2");
+    }
+
+    #[cfg(feature = "face_animator")]
+    fn watermark_free_payload() -> CreateFaceAnimationPayload {
+        CreateFaceAnimationPayload::builder()
+            .audio_sorce("MU:1".to_string())
+            .image_source("MU:2".to_string())
+            .remove_watermark(true)
+            .build()
+            .unwrap()
+    }
+
+    #[cfg(feature = "face_animator")]
+    #[tokio::test]
+    async fn remove_watermark_on_a_free_account_requires_a_subscription() {
+        let server = MockServer::start();
+        server.mock(
+            "GET",
+            "/v1/billing/active_subscriptions",
+            MockResponse::json(json!({"success": true, "active_subscriptions": []})),
+        );
+        let error = server
+            .client_builder()
+            .check_subscription_features(true)
+            .build()
+            .unwrap()
+            .create_facial_animation(watermark_free_payload())
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(
+                error,
+                Error::FeatureRequiresSubscription {
+                    feature: "remove_watermark"
+                }
+            ),
+            "{error:?}"
+        );
+        assert!(server
+            .received("POST", "/animation/face_animation/create")
+            .is_empty());
+    }
+
+    #[cfg(feature = "face_animator")]
+    #[tokio::test]
+    async fn subscription_check_fails_on_an_expired_session() {
+        let server = MockServer::start();
+        server.mock(
+            "GET",
+            "/v1/billing/active_subscriptions",
+            MockResponse::status(401),
+        );
+        let client = server
+            .client_builder()
+            .check_subscription_features(true)
+            .build()
+            .unwrap();
+        client.authenticated.store(true, Ordering::Relaxed);
+        let error = client
+            .create_facial_animation(watermark_free_payload())
+            .await
+            .unwrap_err();
+        assert!(matches!(error, Error::AuthenticationError), "{error:?}");
+        assert!(!client.is_authenticated());
+        assert!(server
+            .received("POST", "/animation/face_animation/create")
+            .is_empty());
+    }
+
+    #[cfg(feature = "face_animator")]
+    #[tokio::test]
+    async fn subscription_check_lets_accounts_without_billing_info_through() {
+        let server = MockServer::start();
+        server.mock(
+            "POST",
+            "/animation/face_animation/create",
+            MockResponse::json(json!({"success": true, "inference_job_token": "JINF:1"})),
+        );
+        server
+            .client_builder()
+            .check_subscription_features(true)
+            .build()
+            .unwrap()
+            .create_facial_animation(watermark_free_payload())
+            .await
+            .unwrap();
+        assert_eq!(
+            server
+                .received("GET", "/v1/billing/active_subscriptions")
+                .len(),
+            1
+        );
+    }
+
+    #[cfg(feature = "face_animator")]
+    #[tokio::test]
+    async fn subscription_check_is_opt_in() {
+        let server = MockServer::start();
+        server.mock(
+            "POST",
+            "/animation/face_animation/create",
+            MockResponse::json(json!({"success": true, "inference_job_token": "JINF:1"})),
+        );
+        server
+            .client()
+            .create_facial_animation(watermark_free_payload())
+            .await
+            .unwrap();
+        assert!(server
+            .received("GET", "/v1/billing/active_subscriptions")
+            .is_empty());
+    }
+
+    #[cfg(feature = "face_animator")]
+    #[tokio::test]
+    async fn uploads_over_the_size_limit_are_rejected_before_sending() {
+        let server = MockServer::start();
+        server.mock(
+            "POST",
+            "/media_uploads/upload_image",
+            MockResponse::json(json!({"success": true, "upload_token": "MU:1"})),
+        );
+        let client = server.client_builder().max_upload_size(4).build().unwrap();
+        let error = client.upload_audio(b"12345").await.unwrap_err();
+        assert!(
+            matches!(error, Error::UploadTooLarge { size: 5, max: 4 }),
+            "{error:?}"
+        );
+        assert!(server.requests().is_empty());
+        let upload = client.upload_image(b"1234").await.unwrap();
+        assert_eq!(upload.upload_token, "MU:1");
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[test]
+    fn parse_error_envelope_reads_api_error_bodies() {
+        let error = parse_error_envelope(&json!({
+            "success": false,
+            "error_type": "rate_limited",
+            "error_message": "too many requests"
+        }));
+        assert!(
+            matches!(&error, Some(Error::ApiError { error_type: Some(error_type), message })
+                if error_type == "rate_limited" && message == "too many requests"),
+            "{error:?}"
+        );
+        let error = parse_error_envelope(&json!({
+            "success": false,
+            "error_reason": "text contains banned words"
+        }));
+        assert!(
+            matches!(&error, Some(Error::ApiError { error_type: None, message })
+                if message == "text contains banned words"),
+            "{error:?}"
+        );
+        let error = parse_error_envelope(&json!({"success": false}));
+        assert!(
+            matches!(&error, Some(Error::ApiError { message, .. }) if message == "unknown error"),
+            "{error:?}"
+        );
+    }
+
+    #[test]
+    fn parse_error_envelope_ignores_successful_or_unrelated_bodies() {
+        assert!(parse_error_envelope(&json!({"success": true, "models": []})).is_none());
+        assert!(parse_error_envelope(&json!({"logged_in": true})).is_none());
+        assert!(parse_error_envelope(&json!([1, 2, 3])).is_none());
+    }
+
+    fn audio_fixture() -> Vec<u8> {
+        (0..200_000).map(|i| (i % 251) as u8).collect()
+    }
+
+    #[tokio::test]
+    async fn audio_stream_yields_the_whole_file() {
+        let server = MockServer::start();
+        server.mock(
+            "GET",
+            "/vocodes-public/audio/result.wav",
+            MockResponse::bytes(audio_fixture()),
+        );
+        let chunks = server
+            .client()
+            .audio_stream("/audio/result.wav")
+            .await
+            .unwrap()
+            .try_collect::<Vec<Bytes>>()
+            .await
+            .unwrap();
+        assert_eq!(chunks.concat(), audio_fixture());
+    }
+
+    // a silent mono 16 bit wav, with a `LIST` chunk before `fmt ` like some encoders write
+    #[cfg(feature = "voice_conversion")]
+    fn wav(sample_rate: u32) -> Vec<u8> {
+        let mut wav = b"RIFF\0\0\0\0WAVE".to_vec();
+        wav.extend_from_slice(b"LIST\x03\0\0\0abc\0");
+        wav.extend_from_slice(b"fmt \x10\0\0\0\x01\0\x01\0");
+        wav.extend_from_slice(&sample_rate.to_le_bytes());
+        wav.extend_from_slice(&(sample_rate * 2).to_le_bytes());
+        wav.extend_from_slice(b"\x02\0\x10\0data\x02\0\0\0\0\0");
+        wav
+    }
+
+    #[cfg(feature = "voice_conversion")]
+    fn voice_conversion_model_with_rate(
+        maybe_target_sample_rate: Option<u32>,
+    ) -> VoiceConversionModel {
+        let mut model = voice_conversion_model("VCM:1");
+        model["maybe_target_sample_rate"] = json!(maybe_target_sample_rate);
+        serde_json::from_value(model).unwrap()
+    }
+
+    #[cfg(feature = "voice_conversion")]
+    #[test]
+    fn matching_sample_rate_is_accepted() {
+        let model = voice_conversion_model_with_rate(Some(40_000));
+        model.validate_sample_rate(&wav(40_000)).unwrap();
+        let model = voice_conversion_model_with_rate(None);
+        model.validate_sample_rate(&wav(22_050)).unwrap();
+        model.validate_sample_rate(b"not a wav").unwrap();
+    }
+
+    #[cfg(feature = "voice_conversion")]
+    #[test]
+    fn mismatching_sample_rate_is_rejected() {
+        let model = voice_conversion_model_with_rate(Some(40_000));
+        let error = model.validate_sample_rate(&wav(16_000)).unwrap_err();
+        assert!(
+            matches!(
+                error,
+                Error::SampleRateMismatch {
+                    expected: 40_000,
+                    actual: 16_000
+                }
+            ),
+            "{error:?}"
+        );
+        assert!(model.validate_sample_rate(b"not a wav").is_err());
+    }
+
+    #[cfg(any(feature = "tts", feature = "face_animator"))]
+    fn describe_job(job: &impl JobLifecycle) -> (String, JobStatus, bool) {
+        (
+            job.job_token().to_string(),
+            *job.status(),
+            job.is_complete(),
+        )
+    }
+
+    #[cfg(feature = "tts")]
+    #[test]
+    fn tts_job_state_implements_job_lifecycle() {
+        let state = serde_json::from_value::<TtsJobResponse>(tts_job("JTINF:1", "started"))
+            .unwrap()
+            .state;
+        assert_eq!(
+            describe_job(&state),
+            ("JTINF:1".to_string(), JobStatus::Started, false)
+        );
+        let state =
+            serde_json::from_value::<TtsJobResponse>(tts_job("JTINF:2", "complete_success"))
+                .unwrap()
+                .state;
+        assert_eq!(
+            describe_job(&state),
+            ("JTINF:2".to_string(), JobStatus::CompleteSuccess, true)
+        );
+    }
+
+    #[cfg(feature = "face_animator")]
+    #[test]
+    fn face_animation_job_state_implements_job_lifecycle() {
+        let state = serde_json::from_value::<FaceAnimationJobResponse>(face_animation_job("dead"))
+            .unwrap()
+            .state;
+        assert_eq!(
+            describe_job(&state),
+            ("JINF:1".to_string(), JobStatus::Dead, true)
+        );
+        let state =
+            serde_json::from_value::<FaceAnimationJobResponse>(face_animation_job("pending"))
+                .unwrap()
+                .state;
+        assert!(!state.is_complete());
+    }
+
+    // replaces the builder's sleep with one that returns immediately and records durations
+    fn record_sleeps(builder: ClientBuilder) -> (ClientBuilder, Arc<Mutex<Vec<Duration>>>) {
+        let sleeps = Arc::new(Mutex::new(Vec::new()));
+        let recorded = sleeps.clone();
+        let builder = builder.sleep_fn(move |duration| {
+            recorded.lock().unwrap().push(duration);
+            async {}
+        });
+        (builder, sleeps)
+    }
+
+    #[cfg(feature = "tts")]
+    #[tokio::test]
+    async fn polls_sleep_with_the_custom_sleep() {
+        let server = MockServer::start();
+        for status in ["pending", "started", "complete_success"] {
+            server.mock(
+                "GET",
+                "/tts/job/JTINF:1",
+                MockResponse::json(tts_job("JTINF:1", status)),
+            );
+        }
+        let (builder, sleeps) = record_sleeps(
+            server
+                .client_builder()
+                .tts_poll_config(PollConfig::new(Duration::from_secs(3))),
+        );
+        builder
+            .build()
+            .unwrap()
+            .poll_tts_job("JTINF:1")
+            .await
+            .unwrap();
+        assert_eq!(*sleeps.lock().unwrap(), [Duration::from_secs(3); 2]);
+    }
+
+    fn rated_voice(
+        model_token: &str,
+        positive_count: u32,
+        negative_count: u32,
+    ) -> serde_json::Value {
+        let mut voice = tts_voice(model_token);
+        voice["user_ratings"] = json!({
+            "positive_count": positive_count,
+            "negative_count": negative_count,
+            "total_count": positive_count + negative_count
+        });
+        voice
+    }
+
+    #[test]
+    fn user_ratings_deserialize_with_defaults() {
+        let voice = serde_json::from_value::<TtsVoice>(rated_voice("TM:1", 3, 1)).unwrap();
+        assert_eq!(voice.user_ratings.positive_count, 3);
+        assert_eq!(voice.user_ratings.negative_count, 1);
+        assert_eq!(voice.user_ratings.total_count, 4);
+        let voice = serde_json::from_value::<TtsVoice>(tts_voice("TM:2")).unwrap();
+        assert_eq!(voice.user_ratings.total_count, 0);
+        assert_eq!(voice.user_ratings.score(), 0.0);
+    }
+
+    #[test]
+    fn user_ratings_score_handles_counts_that_overflow_u32() {
+        let ratings = UserRatings {
+            positive_count: u32::MAX,
+            negative_count: u32::MAX,
+            total_count: u32::MAX,
+        };
+        let score = ratings.score();
+        assert!(score > 0.49 && score < 0.5, "{score}");
+    }
+
+    #[cfg(feature = "voices")]
+    #[tokio::test]
+    async fn voices_sorted_by_rating_puts_well_rated_voices_first() {
+        let server = MockServer::start();
+        server.mock(
+            "GET",
+            "/tts/list",
+            MockResponse::json(json!({
+                "success": true,
+                "models": [
+                    tts_voice("TM:unrated"),
+                    rated_voice("TM:few", 2, 0),
+                    rated_voice("TM:many", 900, 100),
+                    rated_voice("TM:disliked", 10, 90)
+                ]
+            })),
+        );
+        let voices = server.client().voices_sorted_by_rating().await.unwrap();
+        let tokens = voices
+            .iter()
+            .map(|voice| voice.model_token.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(tokens, ["TM:many", "TM:few", "TM:disliked", "TM:unrated"]);
+    }
+
+    #[cfg(feature = "tts")]
+    #[tokio::test]
+    async fn poll_tts_job_to_result_resolves_the_audio_url() {
+        let server = MockServer::start();
+        let mut job = tts_job("JTINF:1", "complete_success");
+        job["state"]["maybe_public_bucket_wav_audio_path"] = json!("/vocodes-public/audio/a.wav");
+        job["state"]["maybe_duration_millis"] = json!(1500);
+        server.mock("GET", "/tts/job/JTINF:1", MockResponse::json(job));
+        let result = server
+            .client()
+            .poll_tts_job_to_result("JTINF:1")
+            .await
+            .unwrap();
+        assert_eq!(
+            result.audio_url,
+            format!("{}/audio/a.wav", server.storage_uri())
+        );
+        assert_eq!(result.maybe_duration, Some(Duration::from_millis(1500)));
+        assert_eq!(result.job_token, "JTINF:1");
+    }
+
+    #[cfg(feature = "face_animator")]
+    fn face_animation_job_requiring_keepalive() -> MockResponse {
+        let mut job = face_animation_job("started");
+        job["state"]["status"]["require_keepalive"] = json!(true);
+        MockResponse::json(job)
+    }
+
+    #[cfg(feature = "face_animator")]
+    #[tokio::test]
+    async fn face_animation_poll_sends_periodic_keepalives() {
+        let server = MockServer::start();
+        server
+            .mock(
+                "GET",
+                "/model_inference/job_status/JINF:1",
+                face_animation_job_requiring_keepalive(),
+            )
+            .mock(
+                "GET",
+                "/model_inference/job_status/JINF:1",
+                face_animation_job_requiring_keepalive(),
+            )
+            .mock(
+                "GET",
+                "/model_inference/job_status/JINF:1",
+                MockResponse::json(face_animation_job("complete_success")),
+            )
+            .mock(
+                "POST",
+                "/model_inference/job_keepalive/JINF:1",
+                MockResponse::json(json!({"success": true})),
+            );
+        server
+            .client()
+            .poll_face_animation_job("JINF:1")
+            .await
+            .unwrap();
+        let paths = server
+            .requests()
+            .into_iter()
+            .map(|request| request.path)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            [
+                "/model_inference/job_status/JINF:1",
+                "/model_inference/job_keepalive/JINF:1",
+                "/model_inference/job_status/JINF:1",
+                "/model_inference/job_keepalive/JINF:1",
+                "/model_inference/job_status/JINF:1",
+            ]
+        );
+    }
+
+    #[cfg(feature = "face_animator")]
+    #[tokio::test]
+    async fn failed_keepalives_are_retried_without_ending_the_poll() {
+        let server = MockServer::start();
+        server
+            .mock(
+                "GET",
+                "/model_inference/job_status/JINF:1",
+                face_animation_job_requiring_keepalive(),
+            )
+            .mock(
+                "GET",
+                "/model_inference/job_status/JINF:1",
+                MockResponse::json(face_animation_job("complete_success")),
+            )
+            .mock(
+                "POST",
+                "/model_inference/job_keepalive/JINF:1",
+                MockResponse::status(503),
+            );
+        let response = server
+            .client()
+            .poll_face_animation_job("JINF:1")
+            .await
+            .unwrap();
+        assert_eq!(response.state.status.status, JobStatus::CompleteSuccess);
+        assert_eq!(
+            server
+                .received("POST", "/model_inference/job_keepalive/JINF:1")
+                .len(),
+            KEEPALIVE_RETRIES + 1
+        );
+    }
+
+    #[cfg(feature = "tts")]
+    #[tokio::test]
+    async fn dry_run_tts_flows_never_reach_the_network() {
+        let server = MockServer::start();
+        let client = server.client_builder().dry_run(true).build().unwrap();
+        let result = client.synthesize("TM:1", "Hello").await.unwrap();
+        assert!(result.audio_url.contains("dry_run:"));
+        client.warm_model("TM:1").await.unwrap();
+        client.delete_tts_result("TR:1").await.unwrap();
+        client.logout().await.unwrap();
+        assert!(server.requests().is_empty());
+    }
+
+    #[cfg(feature = "tts")]
+    #[tokio::test]
+    async fn dry_run_still_validates_tts_inference() {
+        let server = MockServer::start();
+        let client = server.client_builder().dry_run(true).build().unwrap();
+        assert!(matches!(
+            client.tts_inference("TM:1", "").await,
+            Err(Error::EmptyInput)
+        ));
+        let error = client
+            .tts_inference_with(
+                "TM:1",
+                "Hello",
+                TtsInferenceOptions::new().speed(3.0),
+                RequestOptions::default(),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            Error::TtsOptionOutOfRange {
+                option: "speed",
+                ..
+            }
+        ));
+        assert!(server.requests().is_empty());
+    }
+
+    #[cfg(feature = "face_animator")]
+    #[tokio::test]
+    async fn dry_run_face_animation_never_reaches_the_network() {
+        let server = MockServer::start();
+        let client = server.client_builder().dry_run(true).build().unwrap();
+        let response = client
+            .animate_face(b"audio", b"image", CreateFaceAnimationPayload::builder())
+            .await
+            .unwrap();
+        assert_eq!(response.state.status.status, JobStatus::CompleteSuccess);
+        let upload = client.upload_image(b"image").await.unwrap();
+        assert!(client
+            .media_upload_status(upload.upload_token.as_str())
+            .await
+            .unwrap()
+            .maybe_public_bucket_path
+            .is_some());
+        client.face_animation_keepalive("JINF:1").await.unwrap();
+        assert!(server.requests().is_empty());
+    }
+
+    #[cfg(feature = "face_animator")]
+    #[tokio::test]
+    async fn dry_run_still_rejects_oversized_uploads() {
+        let server = MockServer::start();
+        let client = server
+            .client_builder()
+            .dry_run(true)
+            .max_upload_size(4)
+            .build()
+            .unwrap();
+        assert!(matches!(
+            client.upload_audio(b"too large").await,
+            Err(Error::UploadTooLarge { size: 9, max: 4 })
+        ));
+        assert!(server.requests().is_empty());
+    }
+
+    #[cfg(feature = "model_management")]
+    #[tokio::test]
+    async fn dry_run_create_tts_model_returns_a_dry_run_token() {
+        let server = MockServer::start();
+        let model_token = server
+            .client_builder()
+            .dry_run(true)
+            .build()
+            .unwrap()
+            .create_tts_model(create_model_payload())
+            .await
+            .unwrap();
+        assert!(model_token.starts_with("dry_run:"));
+        assert!(server.requests().is_empty());
+    }
+
+    #[cfg(feature = "face_animator")]
+    #[test]
+    fn inference_categories_map_to_variants_and_keep_unknowns() {
+        for (raw, expected) in [
+            ("face_animation", InferenceCategory::FaceAnimation),
+            ("lipsync_animation", InferenceCategory::FaceAnimation),
+            ("tts", InferenceCategory::TextToSpeech),
+            ("text_to_speech", InferenceCategory::TextToSpeech),
+            ("voice_conversion", InferenceCategory::VoiceConversion),
+            (
+                "image_generation",
+                InferenceCategory::Other("image_generation".to_string()),
+            ),
+        ] {
+            let request: FaceAnimationRequest =
+                serde_json::from_value(json!({ "inference_category": raw })).unwrap();
+            assert_eq!(request.inference_category, expected);
+        }
+    }
+
+    #[cfg(feature = "face_animator")]
+    #[test]
+    fn face_animation_job_without_maybe_fields_deserializes() {
+        let mut job = face_animation_job("complete_success");
+        job["state"]["maybe_result"] = json!({
+            "entity_type": "media_file",
+            "entity_token": "MF:1"
+        });
+        let response = serde_json::from_value::<FaceAnimationJobResponse>(job).unwrap();
+        let request = &response.state.request;
+        assert_eq!(request.maybe_model_type, None);
+        assert_eq!(request.maybe_model_token, None);
+        assert_eq!(request.maybe_model_title, None);
+        assert_eq!(request.maybe_raw_inference_text, None);
+        let status = &response.state.status;
+        assert_eq!(status.maybe_first_started_at, None);
+        assert_eq!(status.maybe_extra_status_description, None);
+        assert_eq!(status.maybe_failure_category, None);
+        let result = response.state.maybe_result.unwrap();
+        assert_eq!(result.maybe_public_bucket_media_path, None);
+        assert_eq!(result.maybe_successfully_completed_at, None);
+    }
+
+    #[cfg(feature = "tts")]
+    #[test]
+    fn tts_job_state_without_maybe_fields_deserializes() {
+        let response = serde_json::from_value::<TtsJobResponse>(json!({
+            "success": true,
+            "state": {"job_token": "JTINF:1", "status": "pending"}
+        }))
+        .unwrap();
+        assert_eq!(response.state.maybe_public_bucket_wav_audio_path, None);
+        assert_eq!(response.state.maybe_public_bucket_spectrogram_path, None);
+        assert_eq!(response.state.maybe_duration_millis, None);
+        assert_eq!(response.state.maybe_recommended_poll_interval_ms, None);
+    }
+
+    #[cfg(feature = "wav")]
+    fn stereo_wav(samples: &[i16]) -> Vec<u8> {
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: 44_100,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut wav = std::io::Cursor::new(Vec::new());
+        let mut writer = hound::WavWriter::new(&mut wav, spec).unwrap();
+        for sample in samples {
+            writer.write_sample(*sample).unwrap();
+        }
+        writer.finalize().unwrap();
+        wav.into_inner()
+    }
+
+    #[cfg(feature = "wav")]
+    #[tokio::test]
+    async fn download_audio_samples_decodes_interleaved_samples() {
+        let server = MockServer::start();
+        server.mock(
+            "GET",
+            "/vocodes-public/audio/result.wav",
+            MockResponse::bytes(stereo_wav(&[0, i16::MIN, 16_384, -16_384, 0, 0])),
+        );
+        let (samples, sample_rate) = server
+            .client()
+            .download_audio_samples("/audio/result.wav")
+            .await
+            .unwrap();
+        assert_eq!(sample_rate, 44_100);
+        assert_eq!(samples, [0.0, -1.0, 0.5, -0.5, 0.0, 0.0]);
+    }
+
+    #[cfg(feature = "face_animator")]
+    #[tokio::test]
+    async fn upload_audio_stream_sends_the_whole_reader() {
+        let server = MockServer::start();
+        server.mock(
+            "POST",
+            "/media_uploads/upload_audio",
+            MockResponse::json(json!({"success": true, "upload_token": "MU:1"})),
+        );
+        let audio = audio_fixture();
+        let response = server
+            .client()
+            .upload_audio_stream(
+                futures::io::Cursor::new(audio.clone()),
+                audio.len() as u64,
+                "audio/wav",
+                RequestOptions::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.upload_token, "MU:1");
+        let requests = server.received("POST", "/media_uploads/upload_audio");
+        let body = &requests[0].body;
+        assert!(body
+            .windows(audio.len())
+            .any(|window| window == audio.as_slice()));
+        assert!(requests[0].body_text().contains("Content-Type: audio/wav"));
+    }
+
+    #[cfg(feature = "tts")]
+    #[tokio::test]
+    async fn poll_many_tts_jobs_preserves_the_input_order() {
+        let server = MockServer::start();
+        for (job_token, delay) in [("JTINF:1", 300), ("JTINF:2", 0), ("JTINF:3", 100)] {
+            let mut job = tts_job(job_token, "complete_success");
+            job["state"]["maybe_public_bucket_wav_audio_path"] = json!(format!("/{job_token}.wav"));
+            server
+                .mock(
+                    "GET",
+                    &format!("/tts/job/{job_token}"),
+                    MockResponse::json(tts_job(job_token, "started")),
+                )
+                .mock(
+                    "GET",
+                    &format!("/tts/job/{job_token}"),
+                    MockResponse::json(job).delay(Duration::from_millis(delay)),
+                );
+        }
+        let results = server
+            .client()
+            .poll_many_tts_jobs(
+                vec!["JTINF:1".into(), "JTINF:2".into(), "JTINF:3".into()],
+                3,
+            )
+            .await;
+        let job_tokens = results
+            .into_iter()
+            .map(|result| result.unwrap().job_token)
+            .collect::<Vec<_>>();
+        assert_eq!(job_tokens, ["JTINF:1", "JTINF:2", "JTINF:3"]);
+        assert!(server.max_in_flight() > 1);
+    }
+
+    #[cfg(feature = "tts")]
+    #[tokio::test]
+    async fn stuck_tts_job_fails_after_max_polls() {
+        let server = MockServer::start();
+        server.mock(
+            "GET",
+            "/tts/job/JTINF:1",
+            MockResponse::json(tts_job("JTINF:1", "started")),
+        );
+        let error = server
+            .client_builder()
+            .tts_poll_config(PollConfig::default().max_polls(3))
+            .build()
+            .unwrap()
+            .poll_tts_job("JTINF:1")
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            Error::PollLimitExceeded { ref job_token, polls: 3 } if job_token == "JTINF:1"
+        ));
+        assert_eq!(server.received("GET", "/tts/job/JTINF:1").len(), 3);
+    }
+
+    // needs no features, the minimal ci job relies on it to cover the download helpers
+    #[tokio::test]
+    async fn media_helpers_work_without_features() {
+        let server = MockServer::start();
+        server.mock(
+            "GET",
+            "/vocodes-public/audio/result.wav",
+            MockResponse::bytes(audio_fixture()),
+        );
+        let client = server.client();
+        assert_eq!(
+            client.request_file_url("/audio/result.wav"),
+            format!("{}/audio/result.wav", server.storage_uri())
+        );
+        assert_eq!(
+            client.download_audio("/audio/result.wav").await.unwrap(),
+            audio_fixture()
+        );
+        let chunks = client
+            .audio_stream("/audio/result.wav")
+            .await
+            .unwrap()
+            .try_collect::<Vec<Bytes>>()
+            .await
+            .unwrap();
+        assert_eq!(chunks.concat(), audio_fixture());
+        assert!(matches!(
+            client.download_audio("/audio/missing.wav").await,
+            Err(Error::DownloadFailed { .. } | Error::NotFound)
+        ));
+    }
+
+    #[cfg(feature = "voices")]
+    #[tokio::test]
+    async fn voices_with_meta_captures_the_list_metadata() {
+        let server = MockServer::start();
+        server.mock(
+            "GET",
+            "/tts/list",
+            MockResponse::json(json!({
+                "success": true,
+                "models": [tts_voice("TM:1"), tts_voice("TM:2")],
+                "maybe_pagination": {"total_count": 2, "maybe_next": "cursor-2"},
+                "refresh_interval_millis": 60_000
+            })),
+        );
+        let client = server.client();
+        let response = client.voices_with_meta().await.unwrap();
+        assert_eq!(response.models.len(), 2);
+        let pagination = response.maybe_pagination.unwrap();
+        assert_eq!(pagination.total_count, Some(2));
+        assert_eq!(pagination.maybe_next.as_deref(), Some("cursor-2"));
+        assert_eq!(pagination.maybe_previous, None);
+        assert_eq!(response.refresh_interval_millis, Some(60_000));
+        assert_eq!(client.voices().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn login_retries_a_server_error() {
+        let server = MockServer::start();
+        server
+            .mock("POST", "/login", MockResponse::status(500))
+            .mock(
+                "POST",
+                "/login",
+                MockResponse::json(json!({"success": true})),
+            );
+        let client = server
+            .client_builder()
+            .login("user", "password")
+            .await
+            .unwrap();
+        assert!(client.is_authenticated());
+        assert_eq!(server.received("POST", "/login").len(), 2);
+    }
+
+    #[tokio::test]
+    async fn login_never_retries_bad_credentials() {
+        let server = MockServer::start();
+        server.mock("POST", "/login", MockResponse::status(401));
+        let error = server
+            .client_builder()
+            .login("user", "wrong")
+            .await
+            .unwrap_err();
+        assert!(matches!(error, Error::AuthenticationError));
+        assert_eq!(server.received("POST", "/login").len(), 1);
+    }
+
+    #[test]
+    fn voice_version_fields_deserialize() {
+        let voice = serde_json::from_value::<TtsVoice>(tts_voice("TM:1")).unwrap();
+        assert_eq!(voice.maybe_model_version, None);
+        assert_eq!(voice.updated_at, None);
+
+        let mut voice = tts_voice("TM:1");
+        voice["maybe_model_version"] = json!("v3");
+        voice["created_at"] = json!("2023-01-01T00:00:00Z");
+        voice["updated_at"] = json!("2023-02-01T00:00:00Z");
+        let voice = serde_json::from_value::<TtsVoice>(voice).unwrap();
+        assert_eq!(voice.maybe_model_version.as_deref(), Some("v3"));
+        assert_eq!(voice.created_at.as_deref(), Some("2023-01-01T00:00:00Z"));
+        assert_eq!(voice.updated_at.as_deref(), Some("2023-02-01T00:00:00Z"));
+    }
+
+    #[cfg(feature = "voices")]
+    #[tokio::test]
+    async fn model_details_fetches_the_full_record() {
+        let server = MockServer::start();
+        let mut voice = tts_voice("TM:1");
+        voice["maybe_model_version"] = json!("v3");
+        server.mock(
+            "GET",
+            "/tts/model/TM:1",
+            MockResponse::json(json!({"success": true, "model": voice})),
+        );
+        let voice = server.client().model_details("TM:1").await.unwrap();
+        assert_eq!(voice.model_token, "TM:1");
+        assert_eq!(voice.maybe_model_version.as_deref(), Some("v3"));
+    }
+
+    #[cfg(feature = "tokio")]
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("fakeyou-{}-{name}", Uuid::new_v4()))
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn resumable_download_appends_the_missing_range() {
+        let server = MockServer::start();
+        let audio = audio_fixture();
+        server.mock(
+            "GET",
+            "/vocodes-public/audio/result.wav",
+            MockResponse::bytes(audio[1000..].to_vec())
+                .with_status(206)
+                .header("content-range", "bytes 1000-199999/200000"),
+        );
+        let path = temp_path("partial.wav");
+        tokio::fs::write(&path, &audio[..1000]).await.unwrap();
+        let len = server
+            .client()
+            .download_audio_resumable("/audio/result.wav", &path)
+            .await
+            .unwrap();
+        assert_eq!(len, audio.len() as u64);
+        assert_eq!(tokio::fs::read(&path).await.unwrap(), audio);
+        let requests = server.received("GET", "/vocodes-public/audio/result.wav");
+        assert_eq!(requests[0].header("range"), Some("bytes=1000-"));
+        tokio::fs::remove_file(&path).await.unwrap();
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn resumable_download_of_a_complete_file_is_a_no_op() {
+        let server = MockServer::start();
+        let audio = audio_fixture();
+        server.mock(
+            "GET",
+            "/vocodes-public/audio/result.wav",
+            MockResponse::status(416).header("content-range", "bytes */200000"),
+        );
+        let path = temp_path("complete.wav");
+        tokio::fs::write(&path, &audio).await.unwrap();
+        let len = server
+            .client()
+            .download_audio_resumable("/audio/result.wav", &path)
+            .await
+            .unwrap();
+        assert_eq!(len, audio.len() as u64);
+        assert_eq!(tokio::fs::read(&path).await.unwrap(), audio);
+        tokio::fs::remove_file(&path).await.unwrap();
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn resumable_download_restarts_on_a_mismatched_range() {
+        for mismatch in [
+            // the remote file is shorter than the partial one
+            MockResponse::status(416).header("content-range", "bytes */500"),
+            // the server answered with a different range than the one requested
+            MockResponse::bytes(audio_fixture())
+                .with_status(206)
+                .header("content-range", "bytes 0-199999/200000"),
+        ] {
+            let server = MockServer::start();
+            server
+                .mock("GET", "/vocodes-public/audio/result.wav", mismatch)
+                .mock(
+                    "GET",
+                    "/vocodes-public/audio/result.wav",
+                    MockResponse::bytes(audio_fixture()),
+                );
+            let path = temp_path("stale.wav");
+            tokio::fs::write(&path, vec![0; 1000]).await.unwrap();
+            server
+                .client()
+                .download_audio_resumable("/audio/result.wav", &path)
+                .await
+                .unwrap();
+            assert_eq!(tokio::fs::read(&path).await.unwrap(), audio_fixture());
+            let requests = server.received("GET", "/vocodes-public/audio/result.wav");
+            assert_eq!(requests.len(), 2);
+            assert_eq!(requests[0].header("range"), Some("bytes=1000-"));
+            assert_eq!(requests[1].header("range"), None);
+            tokio::fs::remove_file(&path).await.unwrap();
+        }
+    }
+
+    #[cfg(feature = "tts")]
+    #[tokio::test]
+    async fn poll_first_complete_returns_the_job_that_finishes_first() {
+        let server = MockServer::start();
+        server
+            .mock(
+                "GET",
+                "/tts/job/JTINF:1",
+                MockResponse::json(tts_job("JTINF:1", "started")).delay(Duration::from_millis(300)),
+            )
+            .mock(
+                "GET",
+                "/tts/job/JTINF:2",
+                MockResponse::json(tts_job("JTINF:2", "started")),
+            )
+            .mock(
+                "GET",
+                "/tts/job/JTINF:2",
+                MockResponse::json(tts_job("JTINF:2", "complete_success")),
+            );
+        let (job_token, result) = server
+            .client()
+            .poll_first_complete(vec!["JTINF:1".into(), "JTINF:2".into()])
+            .await
+            .unwrap();
+        assert_eq!(job_token, "JTINF:2");
+        assert_eq!(result.job_token, "JTINF:2");
+        assert_eq!(server.received("GET", "/tts/job/JTINF:2").len(), 2);
+    }
+
+    #[cfg(feature = "tts")]
+    #[tokio::test]
+    async fn poll_first_complete_rejects_an_empty_list() {
+        let server = MockServer::start();
+        let error = server
+            .client()
+            .poll_first_complete(Vec::new())
+            .await
+            .unwrap_err();
+        assert!(matches!(error, Error::NoJobTokens), "{error:?}");
+        assert!(server.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn logout_clears_is_authenticated() {
+        let server = MockServer::start();
+        server
+            .mock(
+                "POST",
+                "/login",
+                MockResponse::json(json!({"success": true})),
+            )
+            .mock(
+                "POST",
+                "/logout",
+                MockResponse::json(json!({"success": true})),
+            );
+        let client = server
+            .client_builder()
+            .login("user", "password")
+            .await
+            .unwrap();
+        assert!(client.is_authenticated());
+        client.logout().await.unwrap();
+        assert!(!client.is_authenticated());
+    }
+
+    #[cfg(feature = "tts")]
+    #[tokio::test]
+    async fn a_rejected_request_clears_is_authenticated() {
+        let server = MockServer::start();
+        server
+            .mock(
+                "POST",
+                "/login",
+                MockResponse::json(json!({"success": true})),
+            )
+            .mock("POST", "/tts/inference", MockResponse::status(401));
+        let client = server
+            .client_builder()
+            .login("user", "password")
+            .await
+            .unwrap();
+        assert!(matches!(
+            client.tts_inference("TM:1", "Hello").await,
+            Err(Error::AuthenticationError)
+        ));
+        assert!(!client.is_authenticated());
+    }
+
+    #[cfg(feature = "tts")]
+    #[tokio::test]
+    async fn synthesize_echoes_the_submitted_text() {
+        let server = MockServer::start();
+        mock_tts_inference(&server);
+        server.mock(
+            "GET",
+            "/tts/job/JTINF:1",
+            MockResponse::json(tts_job("JTINF:1", "complete_success")),
+        );
+        let text = "  Hello, world!\n";
+        let result = server.client().synthesize("TM:1", text).await.unwrap();
+        assert_eq!(result.maybe_inference_text.as_deref(), Some(text));
+        assert_eq!(
+            server.received("POST", "/tts/inference")[0].json()["inference_text"],
+            text
+        );
+    }
+
+    #[cfg(feature = "tts")]
+    #[tokio::test]
+    async fn synthesize_and_download_returns_the_result_and_its_audio() {
+        let server = MockServer::start();
+        mock_tts_inference(&server);
+        server
+            .mock(
+                "GET",
+                "/tts/job/JTINF:1",
+                MockResponse::json(tts_job("JTINF:1", "complete_success")),
+            )
+            .mock(
+                "GET",
+                "/vocodes-public/audio/result.wav",
+                MockResponse::bytes(audio_fixture()),
+            );
+        let (result, audio) = server
+            .client()
+            .synthesize_and_download("TM:1", "Hello")
+            .await
+            .unwrap();
+        assert_eq!(result.job_token, "JTINF:1");
+        assert_eq!(result.maybe_inference_text.as_deref(), Some("Hello"));
+        assert_eq!(audio, audio_fixture());
+    }
+
+    #[test]
+    fn only_pending_is_queued_and_only_started_is_processing() {
+        for status in JobStatus::all() {
+            assert_eq!(
+                status.is_queued(),
+                *status == JobStatus::Pending,
+                "{status:?}"
+            );
+            assert_eq!(
+                status.is_processing(),
+                *status == JobStatus::Started,
+                "{status:?}"
+            );
+            if status.is_queued() || status.is_processing() {
+                assert!(!status.is_terminal(), "{status:?}");
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn default_headers_are_sent_to_every_endpoint() {
+        let server = MockServer::start();
+        server
+            .mock(
+                "POST",
+                "/login",
+                MockResponse::json(json!({"success": true})),
+            )
+            .mock(
+                "GET",
+                "/v1/session",
+                MockResponse::json(json!({"logged_in": true})),
+            )
+            .mock(
+                "GET",
+                "/vocodes-public/audio/result.wav",
+                MockResponse::bytes(audio_fixture()),
+            );
+        let mut default_headers = HeaderMap::new();
+        default_headers.insert("x-tenant-id", "tenant-1".parse().unwrap());
+        default_headers.insert("user-agent", "spoofed".parse().unwrap());
+        let client = server
+            .client_builder()
+            .default_headers(default_headers)
+            .login("user", "password")
+            .await
+            .unwrap();
+        client.ping().await.unwrap();
+        client.download_audio("/audio/result.wav").await.unwrap();
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+        for request in requests {
+            assert_eq!(
+                request.header("x-tenant-id"),
+                Some("tenant-1"),
+                "{}",
+                request.path
+            );
+            assert_eq!(
+                request.header("user-agent"),
+                Some(client.config().user_agent()),
+                "{}",
+                request.path
+            );
+        }
+    }
+
+    #[test]
+    fn upload_sources_serialize_to_the_api_strings() {
+        for (source, expected) in [
+            (UploadSource::File, "file"),
+            (UploadSource::Url, "url"),
+            (UploadSource::MediaUpload, "media_upload"),
+        ] {
+            assert_eq!(serde_json::to_value(source).unwrap(), expected);
+            assert_eq!(source.as_str(), expected);
+        }
+    }
+
+    #[cfg(all(feature = "face_animator", feature = "tokio"))]
+    #[tokio::test(start_paused = true)]
+    async fn face_animation_poll_interval_backs_off_up_to_the_cap() {
+        let server = MockServer::start();
+        for status in [
+            "started",
+            "started",
+            "started",
+            "started",
+            "complete_success",
+        ] {
+            server.mock(
+                "GET",
+                "/model_inference/job_status/JINF:1",
+                MockResponse::json(face_animation_job(status)),
+            );
+        }
+        // no request timeouts, their timers would let the paused clock jump ahead mid request
+        let client = Client::builder()
+            .base_url(server.uri())
+            .connect_timeout(None)
+            .pool_idle_timeout(None)
+            .face_animation_poll_config(
+                PollConfig::new(Duration::from_secs(10)).backoff(2.0, Duration::from_secs(40)),
+            )
+            .build()
+            .unwrap();
+        let started = tokio::time::Instant::now();
+        client.poll_face_animation_job("JINF:1").await.unwrap();
+        // 10s, 20s, then capped at 40s twice
+        assert_eq!(started.elapsed(), Duration::from_secs(110));
+        assert_eq!(
+            server
+                .received("GET", "/model_inference/job_status/JINF:1")
+                .len(),
+            5
+        );
+    }
+
+    #[cfg(feature = "face_animator")]
+    #[tokio::test]
+    async fn failed_face_animation_job_extracts_the_failure_fields() {
+        let server = MockServer::start();
+        let mut job = face_animation_job("complete_failure");
+        job["state"]["status"]["maybe_failure_category"] = json!("face_not_detected");
+        job["state"]["status"]["maybe_extra_status_description"] = json!("no face in the image");
+        server.mock(
+            "GET",
+            "/model_inference/job_status/JINF:1",
+            MockResponse::json(job),
+        );
+        let error = server
+            .client()
+            .poll_face_animation_job("JINF:1")
+            .await
+            .unwrap_err();
+        match error {
+            Error::FaceAnimationJobFailed {
+                job_token,
+                status,
+                maybe_failure_category,
+                maybe_extra_status_description,
+            } => {
+                assert_eq!(job_token, "JINF:1");
+                assert_eq!(status, JobStatus::CompleteFailure);
+                assert_eq!(maybe_failure_category.as_deref(), Some("face_not_detected"));
+                assert_eq!(
+                    maybe_extra_status_description.as_deref(),
+                    Some("no face in the image")
+                );
+            }
+            error => panic!("unexpected error: {error:?}"),
+        }
+    }
+
+    #[cfg(feature = "tts")]
+    #[tokio::test]
+    async fn spectrogram_url_is_resolved_when_the_job_has_one() {
+        let server = MockServer::start();
+        let mut job = tts_job("JTINF:1", "complete_success");
+        job["state"]["maybe_public_bucket_spectrogram_path"] = json!("/audio/result.spec.png");
+        server
+            .mock("GET", "/tts/job/JTINF:1", MockResponse::json(job))
+            .mock(
+                "GET",
+                "/tts/job/JTINF:2",
+                MockResponse::json(tts_job("JTINF:2", "complete_success")),
+            );
+        let client = server.client();
+        let result = client.poll_tts_job_to_result("JTINF:1").await.unwrap();
+        assert_eq!(
+            result.maybe_spectrogram_url,
+            Some(format!("{}/audio/result.spec.png", server.storage_uri()))
+        );
+        let result = client.poll_tts_job_to_result("JTINF:2").await.unwrap();
+        assert_eq!(result.maybe_spectrogram_url, None);
+    }
+
+    #[cfg(feature = "face_animator")]
+    #[tokio::test]
+    async fn face_animation_dimensions_come_from_the_api() {
+        let server = MockServer::start();
+        server.mock(
+            "GET",
+            "/animation/face_animation/dimensions",
+            MockResponse::json(json!({
+                "success": true,
+                "dimensions": ["twitter_square", "tiktok_portrait"]
+            })),
+        );
+        assert_eq!(
+            server.client().face_animation_dimensions().await.unwrap(),
+            ["twitter_square", "tiktok_portrait"]
+        );
+    }
+
+    #[cfg(feature = "face_animator")]
+    #[tokio::test]
+    async fn face_animation_dimensions_fall_back_to_the_known_presets() {
+        let server = MockServer::start();
+        assert_eq!(
+            server.client().face_animation_dimensions().await.unwrap(),
+            FACE_ANIMATION_DIMENSIONS
+        );
+    }
+
+    #[cfg(feature = "voices")]
+    #[tokio::test]
+    async fn clones_share_the_voice_cache() {
+        let server = MockServer::start();
+        mock_voice_list(&server);
+        let client = server
+            .client_builder()
+            .voices_cache_ttl(Some(Duration::from_secs(60)))
+            .build()
+            .unwrap();
+        let clone = client.clone();
+        client.voices().await.unwrap();
+        let voices = clone.voices().await.unwrap();
+        assert_eq!(voices[0].model_token, "TM:1");
+        assert_eq!(server.received("GET", "/tts/list").len(), 1);
+    }
+
+    #[tokio::test]
+    async fn clones_share_the_session_state() {
+        let server = MockServer::start();
+        server
+            .mock(
+                "POST",
+                "/login",
+                MockResponse::json(json!({"success": true})),
+            )
+            .mock(
+                "POST",
+                "/logout",
+                MockResponse::json(json!({"success": true})),
+            );
+        let client = server
+            .client_builder()
+            .login("user", "password")
+            .await
+            .unwrap();
+        let clone = client.clone();
+        assert!(clone.is_authenticated());
+        clone.logout().await.unwrap();
+        assert!(!client.is_authenticated());
+    }
+
+    #[cfg(feature = "leaderboard")]
+    #[tokio::test]
+    async fn leaderboard_returns_the_requested_kind() {
+        let server = MockServer::start();
+        server.mock(
+            "GET",
+            "/leaderboard",
+            MockResponse::json(json!({
+                "success": true,
+                "tts_leaderboard": [{
+                    "creator_user_token": "U:1",
+                    "username": "voicemaker",
+                    "display_name": "Voice Maker",
+                    "gravatar_hash": "abc123",
+                    "uploaded_count": 42
+                }],
+                "w2l_leaderboard": [{
+                    "creator_user_token": "U:2",
+                    "username": "animator",
+                    "display_name": "Animator",
+                    "uploaded_count": 7
+                }]
+            })),
+        );
+        let client = server.client();
+        let tts = client.leaderboard(LeaderboardKind::Tts).await.unwrap();
+        assert_eq!(tts.len(), 1);
+        assert_eq!(tts[0].username, "voicemaker");
+        assert_eq!(tts[0].display_name, "Voice Maker");
+        assert_eq!(tts[0].gravatar_hash.as_deref(), Some("abc123"));
+        assert_eq!(tts[0].uploaded_count, 42);
+        let face_animation = client
+            .leaderboard(LeaderboardKind::FaceAnimation)
+            .await
+            .unwrap();
+        assert_eq!(face_animation[0].creator_user_token, "U:2");
+        assert_eq!(face_animation[0].gravatar_hash, None);
+    }
+
+    #[cfg(feature = "face_animator")]
+    #[test]
+    fn raw_inference_text_is_only_set_for_tts_driven_animations() {
+        let response = serde_json::from_value::<FaceAnimationJobResponse>(face_animation_job(
+            "complete_success",
+        ))
+        .unwrap();
+        assert_eq!(response.state.request.maybe_raw_inference_text, None);
+
+        let mut job = face_animation_job("complete_success");
+        job["state"]["request"]["maybe_raw_inference_text"] = json!("Hello there");
+        let response = serde_json::from_value::<FaceAnimationJobResponse>(job).unwrap();
+        assert_eq!(
+            response.state.request.maybe_raw_inference_text.as_deref(),
+            Some("Hello there")
+        );
+    }
+
+    #[test]
+    fn rate_limit_wait_paces_the_remaining_requests() {
+        let headers = |limit: Option<u64>, remaining: u64| ResponseHeaders {
+            rate_limit_limit: limit,
+            rate_limit_remaining: Some(remaining),
+            rate_limit_reset: Some(60),
+            ..ResponseHeaders::default()
+        };
+        assert_eq!(headers(Some(100), 50).rate_limit_wait(), None);
+        assert_eq!(headers(None, 5).rate_limit_wait(), None);
+        assert_eq!(
+            headers(Some(100), 5).rate_limit_wait(),
+            Some(Duration::from_secs(10))
+        );
+        assert_eq!(
+            headers(None, 0).rate_limit_wait(),
+            Some(Duration::from_secs(60))
+        );
+    }
+
+    #[test]
+    fn relative_rate_limit_reset_counts_from_when_the_response_arrived() {
+        let headers = ResponseHeaders {
+            rate_limit_remaining: Some(0),
+            rate_limit_reset: Some(30),
+            received_at: Some(Instant::now() - Duration::from_secs(10)),
+            ..ResponseHeaders::default()
+        };
+        let wait = headers.rate_limit_wait().unwrap();
+        assert!(
+            (Duration::from_secs(19)..=Duration::from_secs(20)).contains(&wait),
+            "{wait:?}"
+        );
+    }
 
-#[derive(Clone, Debug, Deserialize)]
-pub struct TtsVoice {
-    pub model_token: String,
-    pub tts_model_type: String,
-    pub title: String,
-    pub ietf_language_tag: String,
-    pub ietf_primary_language_subtag: String,
-}
+    #[cfg(feature = "tts")]
+    #[tokio::test]
+    async fn synthesize_batch_pauses_once_no_requests_are_remaining() {
+        let server = Arc::new(MockServer::start());
+        for remaining in ["2", "1", "0", "9"] {
+            server.mock(
+                "POST",
+                "/tts/inference",
+                MockResponse::json(json!({"success": true, "inference_job_token": "JTINF:1"}))
+                    .header("x-ratelimit-remaining", remaining)
+                    .header("x-ratelimit-reset", "30"),
+            );
+        }
+        server.mock(
+            "GET",
+            "/tts/job/JTINF:1",
+            MockResponse::json(tts_job("JTINF:1", "complete_success")),
+        );
+        // records each sleep with the number of jobs submitted before it
+        let sleeps = Arc::new(Mutex::new(Vec::new()));
+        let (recorded, observed) = (sleeps.clone(), server.clone());
+        let client = server
+            .client_builder()
+            .sleep_fn(move |duration| {
+                let submitted = observed.received("POST", "/tts/inference").len();
+                recorded.lock().unwrap().push((duration, submitted));
+                async {}
+            })
+            .build()
+            .unwrap();
+        let texts = ["one", "two", "three", "four"].map(String::from).to_vec();
+        let results = client.synthesize_batch("TM:1", texts, 4).await;
+        assert!(results.iter().all(Result::is_ok));
+        let sleeps = sleeps.lock().unwrap();
+        assert_eq!(sleeps.len(), 1, "{sleeps:?}");
+        let (wait, submitted) = sleeps[0];
+        assert_eq!(submitted, 3);
+        assert!(
+            (Duration::from_secs(29)..=Duration::from_secs(30)).contains(&wait),
+            "{wait:?}"
+        );
+        assert_eq!(server.received("POST", "/tts/inference").len(), 4);
+    }
 
-#[derive(Clone, Debug, Serialize)]
-pub struct UploadFilePayload<'a> {
-    uuid_idempotency_token: Uuid,
-    file: &'a [u8],
-    source: &'a str,
-}
+    #[cfg(feature = "tts")]
+    #[tokio::test]
+    async fn tts_result_fetches_the_stored_result() {
+        let server = MockServer::start();
+        server.mock(
+            "GET",
+            "/tts/result/TR:1",
+            MockResponse::json(json!({
+                "success": true,
+                "result": {
+                    "tts_result_token": "TR:1",
+                    "raw_inference_text": "Hello",
+                    "tts_model_token": "TM:1",
+                    "tts_model_title": "Voice",
+                    "public_bucket_wav_audio_path": "/audio/result.wav",
+                    "duration_millis": 1200,
+                    "created_at": "2023-01-01T00:00:00Z"
+                }
+            })),
+        );
+        let client = server.client();
+        let result = client.tts_result("TR:1").await.unwrap();
+        assert_eq!(result.tts_result_token, "TR:1");
+        assert_eq!(result.raw_inference_text, "Hello");
+        assert_eq!(result.tts_model_token, "TM:1");
+        assert_eq!(result.tts_model_title.as_deref(), Some("Voice"));
+        assert_eq!(result.public_bucket_wav_audio_path, "/audio/result.wav");
+        assert_eq!(result.maybe_creator_username, None);
+        assert_eq!(result.duration_millis, Some(1200));
+        assert_eq!(result.created_at, "2023-01-01T00:00:00Z");
+        assert!(matches!(
+            client.tts_result("TR:missing").await,
+            Err(Error::NotFound)
+        ));
+    }
 
-#[derive(Clone, Debug, Deserialize)]
-pub struct UploadFileResponse {
-    pub success: bool,
-    pub upload_token: String,
-}
+    #[cfg(feature = "face_animator")]
+    fn media_upload(maybe_public_bucket_path: Option<&str>) -> MockResponse {
+        MockResponse::json(json!({
+            "success": true,
+            "media_upload": {
+                "token": "MU:1",
+                "media_type": "audio",
+                "maybe_public_bucket_path": maybe_public_bucket_path,
+                "created_at": "2023-01-01T00:00:00Z"
+            }
+        }))
+    }
 
-#[cfg(feature = "face_animator")]
-#[derive(Builder, Clone, Debug, Serialize)]
-pub struct CreateFaceAnimationPayload {
-    #[builder(setter(custom))]
-    audio_sorce: FaceAnimationMediaSource,
-    #[builder(default = "\"twitter_square\".to_string()")]
-    dimensions: String,
-    disable_face_enhancement: bool,
-    #[builder(setter(custom))]
-    image_source: FaceAnimationMediaSource,
-    make_still: bool,
-    remove_watermark: bool,
-    #[builder(default = "Uuid::new_v4()")]
-    uuid_idempotency_token: Uuid,
-}
+    #[cfg(feature = "face_animator")]
+    #[tokio::test]
+    async fn wait_for_media_upload_polls_until_processed() {
+        let server = MockServer::start();
+        server
+            .mock("GET", "/media_uploads/get/MU:1", media_upload(None))
+            .mock("GET", "/media_uploads/get/MU:1", media_upload(None))
+            .mock(
+                "GET",
+                "/media_uploads/get/MU:1",
+                media_upload(Some("/media/audio.wav")),
+            );
+        let client = server.client();
+        assert!(!client.media_upload_status("MU:1").await.unwrap().is_ready());
+        let state = client.wait_for_media_upload("MU:1").await.unwrap();
+        assert!(state.is_ready());
+        assert_eq!(
+            state.maybe_public_bucket_path.as_deref(),
+            Some("/media/audio.wav")
+        );
+        assert_eq!(server.received("GET", "/media_uploads/get/MU:1").len(), 3);
+    }
 
-#[cfg(feature = "face_animator")]
-impl CreateFaceAnimationPayload {
-    pub fn audio_sorce(&mut self, maybe_media_upload_token: String) {
-        self.audio_sorce = FaceAnimationMediaSource {
-            maybe_media_upload_token,
+    #[cfg(feature = "tts")]
+    #[test]
+    fn estimate_tts_cost_scales_with_the_text_length() {
+        let server = MockServer::start();
+        let client = server
+            .client_builder()
+            .tts_credits_per_character(Some(0.5))
+            .build()
+            .unwrap();
+        let text = "a".repeat(150);
+        let estimate = client.estimate_tts_cost("TM:1", &text).unwrap();
+        assert_eq!(estimate.characters, 150);
+        assert_eq!(estimate.maybe_credits, Some(75.0));
+        assert_eq!(estimate.estimated_duration, Duration::from_secs(10));
+        let estimate = server.client().estimate_tts_cost("TM:1", &text).unwrap();
+        assert_eq!(estimate.maybe_credits, None);
+        assert!(server.requests().is_empty());
+    }
+
+    #[cfg(all(feature = "tts", feature = "tokio"))]
+    #[tokio::test(start_paused = true)]
+    async fn dropping_a_poll_mid_sleep_stops_it() {
+        let server = MockServer::start();
+        server.mock(
+            "GET",
+            "/tts/job/JTINF:1",
+            MockResponse::json(tts_job("JTINF:1", "started")),
+        );
+        let sleeps = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let started_sleeps = sleeps.clone();
+        let client = Client::builder()
+            .base_url(server.uri())
+            .connect_timeout(None)
+            .pool_idle_timeout(None)
+            .sleep_fn(move |duration| {
+                started_sleeps.fetch_add(1, Ordering::SeqCst);
+                tokio::time::sleep(duration)
+            })
+            .build()
+            .unwrap();
+        let mut poll = Box::pin(client.poll_tts_job("JTINF:1"));
+        let second_sleep = async {
+            while sleeps.load(Ordering::SeqCst) < 2 {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
         };
+        tokio::select! {
+            _ = &mut poll => panic!("the job never completes"),
+            _ = second_sleep => {}
+        }
+        assert_eq!(client.active_polls.lock().unwrap().handles.len(), 1);
+        drop(poll);
+        assert!(client.active_polls.lock().unwrap().handles.is_empty());
+        tokio::time::sleep(Duration::from_secs(60)).await;
+        assert_eq!(server.received("GET", "/tts/job/JTINF:1").len(), 2);
+        assert_eq!(sleeps.load(Ordering::SeqCst), 2);
     }
 
-    pub fn image_source(&mut self, maybe_media_upload_token: String) {
-        self.image_source = FaceAnimationMediaSource {
-            maybe_media_upload_token,
+    #[test]
+    fn fresh_and_replayed_inference_responses_deserialize() {
+        let fresh = serde_json::from_value::<TtsInferenceResponse>(json!({
+            "success": true,
+            "inference_job_token": "JTINF:1",
+            "inference_job_token_type": "generic"
+        }))
+        .unwrap();
+        assert_eq!(fresh.inference_job_token.as_deref(), Some("JTINF:1"));
+        assert!(!fresh.was_replay);
+
+        let replayed = serde_json::from_value::<TtsInferenceResponse>(json!({
+            "success": false,
+            "error_reason": "idempotency token already used",
+            "original_inference_job_token": "JTINF:1"
+        }))
+        .unwrap();
+        assert!(replayed.success);
+        assert_eq!(replayed.inference_job_token.as_deref(), Some("JTINF:1"));
+        assert_eq!(replayed.error_reason, None);
+        assert!(replayed.was_replay);
+    }
+
+    #[cfg(feature = "tts")]
+    #[tokio::test]
+    async fn replayed_submission_is_not_an_error() {
+        let server = MockServer::start();
+        server.mock(
+            "POST",
+            "/tts/inference",
+            MockResponse::json(json!({
+                "success": false,
+                "error_reason": "idempotency token already used",
+                "maybe_original_inference_job_token": "JTINF:1"
+            })),
+        );
+        let response = server
+            .client()
+            .tts_inference("TM:1", "Hello")
+            .await
+            .unwrap();
+        assert_eq!(response.inference_job_token.as_deref(), Some("JTINF:1"));
+        assert!(response.was_replay);
+    }
+
+    #[cfg(feature = "tts")]
+    #[tokio::test]
+    async fn error_tokens_are_redacted_when_enabled() {
+        let server = MockServer::start();
+        server.mock(
+            "GET",
+            "/tts/job/JTINF:abcdef1234",
+            MockResponse::json(tts_job("JTINF:abcdef1234", "complete_failure")),
+        );
+        for (redact_error_tokens, expected) in [(true, "…1234"), (false, "JTINF:abcdef1234")] {
+            let error = server
+                .client_builder()
+                .redact_error_tokens(redact_error_tokens)
+                .build()
+                .unwrap()
+                .poll_tts_job("JTINF:abcdef1234")
+                .await
+                .unwrap_err();
+            assert!(
+                matches!(&error, Error::TtsJobFailed { job_token, .. } if job_token == expected),
+                "{error:?}"
+            );
+            assert_eq!(
+                error.to_string().contains("JTINF:abcdef1234"),
+                !redact_error_tokens
+            );
+            assert_eq!(
+                format!("{error:?}").contains("JTINF:abcdef1234"),
+                !redact_error_tokens
+            );
+        }
+    }
+
+    #[cfg(feature = "tts")]
+    #[tokio::test]
+    async fn recent_tts_results_resolve_their_audio_urls() {
+        let server = MockServer::start();
+        let entry = |token: &str, path: &str| {
+            json!({
+                "tts_result_token": token,
+                "tts_model_token": "TM:1",
+                "raw_inference_text": "Hello",
+                "public_bucket_wav_audio_path": path,
+                "created_at": "2023-01-01T00:00:00Z"
+            })
         };
+        server.mock(
+            "GET",
+            "/tts/results",
+            MockResponse::json(json!({
+                "success": true,
+                "results": [
+                    entry("TR:1", "/media/a/one.wav"),
+                    entry("TR:2", "/vocodes-public/media/b/two.wav")
+                ]
+            })),
+        );
+        let results = server.client().recent_tts_results(2).await.unwrap();
+        assert_eq!(
+            server.received("GET", "/tts/results")[0].query.as_deref(),
+            Some("limit=2")
+        );
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].tts_result_token, "TR:1");
+        assert_eq!(results[0].tts_model_title, None);
+        assert_eq!(
+            results[0].audio_url,
+            format!("{}/media/a/one.wav", server.storage_uri())
+        );
+        assert_eq!(
+            results[1].audio_url,
+            format!("{}/media/b/two.wav", server.storage_uri())
+        );
     }
-}
 
-#[cfg(feature = "face_animator")]
-#[derive(Clone, Debug, Serialize)]
-pub struct FaceAnimationMediaSource {
-    maybe_media_upload_token: String,
-}
+    #[cfg(feature = "voices")]
+    #[tokio::test]
+    async fn voices_filtered_sends_server_side_filters_as_query_params() {
+        let server = MockServer::start();
+        let mut other_title = tts_voice("TM:2");
+        other_title["title"] = json!("Narrator");
+        server.mock(
+            "GET",
+            "/tts/list",
+            MockResponse::json(json!({
+                "success": true,
+                "models": [tts_voice("TM:1"), other_title]
+            })),
+        );
+        let voices = server
+            .client()
+            .voices_filtered(
+                VoiceFilter::new()
+                    .language("en-US")
+                    .model_type("tacotron2")
+                    .title_contains("VOICE"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            server.received("GET", "/tts/list")[0].query.as_deref(),
+            Some("language=en-US&tts_model_type=tacotron2")
+        );
+        assert_eq!(voices.len(), 1);
+        assert_eq!(voices[0].model_token, "TM:1");
+    }
 
-#[cfg(feature = "face_animator")]
-#[derive(Clone, Debug, Deserialize)]
-pub struct CreateFaceAnimationResponse {
-    pub success: bool,
-    pub inference_job_token: String,
-}
+    #[cfg(feature = "tts")]
+    #[tokio::test]
+    async fn recommended_poll_interval_replaces_the_configured_one() {
+        let server = MockServer::start();
+        let mut job = tts_job("JTINF:1", "started");
+        job["state"]["maybe_recommended_poll_interval_ms"] = json!(3000);
+        server
+            .mock("GET", "/tts/job/JTINF:1", MockResponse::json(job))
+            .mock(
+                "GET",
+                "/tts/job/JTINF:1",
+                MockResponse::json(tts_job("JTINF:1", "started")),
+            )
+            .mock(
+                "GET",
+                "/tts/job/JTINF:1",
+                MockResponse::json(tts_job("JTINF:1", "complete_success")),
+            );
+        let (builder, sleeps) = record_sleeps(
+            server
+                .client_builder()
+                .tts_poll_config(PollConfig::new(Duration::from_secs(8))),
+        );
+        builder
+            .build()
+            .unwrap()
+            .poll_tts_job("JTINF:1")
+            .await
+            .unwrap();
+        assert_eq!(
+            *sleeps.lock().unwrap(),
+            [Duration::from_secs(3), Duration::from_secs(8)]
+        );
+    }
 
-#[cfg(feature = "face_animator")]
-#[derive(Clone, Debug, Deserialize)]
-pub struct FaceAnimationJobResponse {
-    pub success: bool,
-    pub state: FaceAnimationJobState,
-}
+    #[cfg(feature = "voices")]
+    #[tokio::test]
+    async fn model_exists_checks_the_detail_endpoint() {
+        let server = MockServer::start();
+        server.mock(
+            "GET",
+            "/tts/model/TM:1",
+            MockResponse::json(json!({"success": true, "model": tts_voice("TM:1")})),
+        );
+        let client = server.client();
+        assert!(client.model_exists("TM:1").await.unwrap());
+        assert!(!client.model_exists("TM:unknown").await.unwrap());
+    }
 
-#[cfg(feature = "face_animator")]
-#[derive(Clone, Debug, Deserialize)]
-pub struct FaceAnimationJobState {
-    pub job_token: String,
-    pub request: FaceAnimationRequest,
-    pub status: FaceAnimationStatus,
-    pub maybe_result: Option<FaceAnimationRequest>,
-    pub created_at: String,
-    pub updated_at: String,
-}
+    #[cfg(feature = "voices")]
+    #[tokio::test]
+    async fn model_exists_uses_a_fresh_voice_cache() {
+        let server = MockServer::start();
+        mock_voice_list(&server);
+        let client = server
+            .client_builder()
+            .voices_cache_ttl(Some(Duration::from_secs(60)))
+            .build()
+            .unwrap();
+        client.voices().await.unwrap();
+        assert!(client.model_exists("TM:1").await.unwrap());
+        assert!(!client.model_exists("TM:unknown").await.unwrap());
+        assert_eq!(server.requests().len(), 1);
+    }
 
-#[cfg(feature = "face_animator")]
-#[derive(Clone, Debug, Deserialize)]
-pub struct FaceAnimationRequest {
-    pub inference_category: String,
-    pub maybe_model_type: String,
-    pub maybe_model_token: Option<String>,
-    pub maybe_model_title: String,
-    pub maybe_raw_inference_text: Option<String>,
-}
+    #[cfg(all(feature = "blocking", feature = "tts"))]
+    fn blocking_client(server: &MockServer) -> Client {
+        Client::builder()
+            .base_url(server.uri())
+            .file_storage_base_url(server.storage_uri())
+            .blocking_sleep()
+            .tts_poll_config(PollConfig::new(Duration::from_millis(20)))
+            .build()
+            .unwrap()
+    }
 
-#[cfg(feature = "face_animator")]
-#[derive(Clone, Debug, Deserialize)]
-pub struct FaceAnimationStatus {
-    pub status: JobStatus,
-    pub maybe_extra_status_description: Option<String>,
-    pub maybe_assigned_worker: String,
-    pub maybe_assigned_cluster: String,
-    pub maybe_first_started_at: String,
-    pub attempt_count: u32,
-    pub require_keepalive: bool,
-    pub maybe_failure_category: Option<String>,
-}
+    #[cfg(all(feature = "blocking", feature = "tts"))]
+    fn block_on<F: Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
 
-#[cfg(feature = "face_animator")]
-#[derive(Clone, Debug, Deserialize)]
-pub struct FaceAnimationResult {
-    pub entity_type: String,
-    pub entity_token: String,
-    pub maybe_public_bucket_media_path: String,
-    pub maybe_successfully_completed_at: String,
+    #[cfg(all(feature = "blocking", feature = "tts"))]
+    #[test]
+    fn blocking_poll_runs_to_completion() {
+        let server = MockServer::start();
+        for status in ["pending", "started", "complete_success"] {
+            server.mock(
+                "GET",
+                "/tts/job/JTINF:1",
+                MockResponse::json(tts_job("JTINF:1", status)),
+            );
+        }
+        let client = blocking_client(&server);
+        let started = Instant::now();
+        let result = block_on(client.poll_tts_job_to_result("JTINF:1")).unwrap();
+        assert_eq!(result.job_token, "JTINF:1");
+        assert!(started.elapsed() >= Duration::from_millis(40));
+        assert_eq!(server.received("GET", "/tts/job/JTINF:1").len(), 3);
+    }
+
+    #[cfg(all(feature = "blocking", feature = "tts", feature = "tokio"))]
+    #[test]
+    fn blocking_synthesize_deadline_completes_before_the_deadline() {
+        let server = MockServer::start();
+        mock_tts_inference(&server);
+        server
+            .mock(
+                "GET",
+                "/tts/job/JTINF:1",
+                MockResponse::json(tts_job("JTINF:1", "started")),
+            )
+            .mock(
+                "GET",
+                "/tts/job/JTINF:1",
+                MockResponse::json(tts_job("JTINF:1", "complete_success")),
+            );
+        let client = blocking_client(&server);
+        let deadline = std::time::Instant::now() + Duration::from_secs(10);
+        let result = block_on(client.synthesize_deadline("TM:1", "Hello", deadline)).unwrap();
+        assert_eq!(result.job_token, "JTINF:1");
+    }
+
+    #[cfg(feature = "face_animator")]
+    #[tokio::test]
+    #[allow(deprecated)]
+    async fn deprecated_async_builder_matches_the_sync_one() {
+        let server = MockServer::start();
+        let client = server.client();
+        let mut from_client = client.create_facial_animation_builder().await;
+        let from_client = from_client
+            .audio_sorce("MU:1".to_string())
+            .image_source("MU:2".to_string())
+            .make_still(true)
+            .build()
+            .unwrap();
+        let payload = CreateFaceAnimationPayload::builder()
+            .audio_sorce("MU:1".to_string())
+            .image_source("MU:2".to_string())
+            .make_still(true)
+            .build()
+            .unwrap();
+        let without_token = |payload: CreateFaceAnimationPayload| {
+            let mut payload = serde_json::to_value(payload).unwrap();
+            payload
+                .as_object_mut()
+                .unwrap()
+                .remove("uuid_idempotency_token");
+            payload
+        };
+        assert_eq!(without_token(from_client), without_token(payload));
+        assert!(server.requests().is_empty());
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn results_download_into_a_dir_with_unique_names() {
+        let server = MockServer::start();
+        server
+            .mock(
+                "GET",
+                "/vocodes-public/audio/one.wav",
+                MockResponse::bytes(b"one".to_vec()),
+            )
+            .mock(
+                "GET",
+                "/vocodes-public/audio/two.wav",
+                MockResponse::bytes(b"two".to_vec()),
+            );
+        let result = |token: &str| TtsResult {
+            job_token: token.to_string(),
+            audio_url: format!("/audio/{token}.wav"),
+            maybe_spectrogram_url: None,
+            maybe_duration: None,
+            maybe_inference_text: None,
+        };
+        let results = [result("one"), result("two"), result("missing")];
+        let dir = temp_path("results");
+        let paths = server
+            .client()
+            .download_results_to_dir(&results, &dir, |_| "result.wav".to_string())
+            .await
+            .unwrap();
+        assert_eq!(paths.len(), 3);
+        let first = paths[0].as_ref().unwrap();
+        let second = paths[1].as_ref().unwrap();
+        assert_ne!(first, second);
+        assert_eq!(std::fs::read(first).unwrap(), b"one");
+        assert_eq!(std::fs::read(second).unwrap(), b"two");
+        assert!(matches!(
+            paths[2],
+            Err(Error::DownloadFailed { status, .. }) if status == StatusCode::NOT_FOUND
+        ));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(feature = "tts")]
+    #[tokio::test]
+    async fn banned_word_is_rejected_before_submitting() {
+        let server = MockServer::start();
+        mock_tts_inference(&server);
+        let client = server
+            .client_builder()
+            .banned_word_filter(BannedWordFilter::new(["Heck"]))
+            .build()
+            .unwrap();
+        let err = client
+            .tts_inference("TM:1", "well, HECK it")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::BannedWord { ref word } if word == "heck"));
+        assert!(server.requests().is_empty());
+        client.tts_inference("TM:1", "heckle").await.unwrap();
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[cfg(feature = "tts")]
+    #[tokio::test]
+    async fn client_error_keeps_the_message_of_its_body() {
+        let server = MockServer::start();
+        server.mock(
+            "POST",
+            "/tts/inference",
+            MockResponse::json(json!({
+                "success": false,
+                "error_reason": "text contains banned words"
+            }))
+            .with_status(400),
+        );
+        let err = server
+            .client()
+            .tts_inference("TM:1", "hello")
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&err, Error::ApiError { message, .. } if message == "text contains banned words"),
+            "{err:?}"
+        );
+    }
+
+    #[cfg(feature = "voice_conversion")]
+    fn voice_conversion_job(job_token: &str) -> MockResponse {
+        MockResponse::json(json!({
+            "success": true,
+            "state": {
+                "job_token": job_token,
+                "status": {"status": "complete_success"},
+                "maybe_result": {
+                    "maybe_public_bucket_media_path": format!("/media/{job_token}.wav")
+                }
+            }
+        }))
+    }
+
+    #[cfg(feature = "voice_conversion")]
+    #[tokio::test]
+    async fn voice_conversion_batch_converts_a_shared_upload() {
+        let server = MockServer::start();
+        server
+            .mock(
+                "POST",
+                "/v1/voice_conversion/inference",
+                MockResponse::json(json!({"success": true, "inference_job_token": "JINF:1"})),
+            )
+            .mock(
+                "POST",
+                "/v1/voice_conversion/inference",
+                MockResponse::json(json!({"success": true, "inference_job_token": "JINF:2"})),
+            )
+            .mock(
+                "GET",
+                "/model_inference/job_status/JINF:1",
+                voice_conversion_job("JINF:1"),
+            )
+            .mock(
+                "GET",
+                "/model_inference/job_status/JINF:2",
+                voice_conversion_job("JINF:2"),
+            );
+        let results = server
+            .client()
+            .voice_conversion_batch("MU:1", vec!["VCM:1".to_string(), "VCM:2".to_string()], 1)
+            .await;
+        let urls = results
+            .into_iter()
+            .map(|result| result.unwrap().audio_url)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            urls,
+            [
+                format!("{}/media/JINF:1.wav", server.storage_uri()),
+                format!("{}/media/JINF:2.wav", server.storage_uri()),
+            ]
+        );
+        let submissions = server.received("POST", "/v1/voice_conversion/inference");
+        let models = submissions
+            .iter()
+            .map(|request| {
+                let body = request.json();
+                assert_eq!(body["source_media_upload_token"], "MU:1");
+                body["voice_conversion_model_token"]
+                    .as_str()
+                    .unwrap()
+                    .to_string()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(models, ["VCM:1", "VCM:2"]);
+        assert_ne!(
+            submissions[0].json()["uuid_idempotency_token"],
+            submissions[1].json()["uuid_idempotency_token"]
+        );
+    }
+
+    #[cfg(feature = "voice_conversion")]
+    #[tokio::test]
+    async fn voice_conversion_with_applies_the_request_options() {
+        let server = MockServer::start();
+        server
+            .mock(
+                "POST",
+                "/v1/voice_conversion/inference",
+                MockResponse::json(json!({"success": true, "inference_job_token": "JINF:1"})),
+            )
+            .mock(
+                "GET",
+                "/model_inference/job_status/JINF:1",
+                voice_conversion_job("JINF:1"),
+            );
+        let idempotency_token = Uuid::new_v4();
+        server
+            .client()
+            .voice_conversion_with(
+                "VCM:1",
+                "MU:1",
+                RequestOptions::new()
+                    .idempotency_token(idempotency_token)
+                    .correlation_id("conversion-1"),
+            )
+            .await
+            .unwrap();
+        let submission = &server.received("POST", "/v1/voice_conversion/inference")[0];
+        assert_eq!(submission.header("x-correlation-id"), Some("conversion-1"));
+        assert_eq!(
+            submission.json()["uuid_idempotency_token"],
+            idempotency_token.to_string()
+        );
+    }
+
+    #[cfg(feature = "voice_conversion")]
+    #[tokio::test]
+    async fn dry_run_voice_conversion_sends_nothing() {
+        let server = MockServer::start();
+        let client = server.client_builder().dry_run(true).build().unwrap();
+        let result = client.voice_conversion("VCM:1", "MU:1").await.unwrap();
+        assert!(result.job_token.starts_with(DRY_RUN_TOKEN_PREFIX));
+        assert!(result
+            .audio_url
+            .ends_with(&format!("/dry_run/{}.wav", result.job_token)));
+        assert!(server.requests().is_empty());
+    }
+
+    #[cfg(all(feature = "voice_conversion", feature = "face_animator"))]
+    #[tokio::test]
+    async fn voice_conversion_source_is_checked_before_uploading() {
+        let server = MockServer::start();
+        server.mock(
+            "POST",
+            "/media_uploads/upload_audio",
+            MockResponse::json(json!({"success": true, "upload_token": "MU:1"})),
+        );
+        let client = server.client();
+        let model = voice_conversion_model_with_rate(Some(40_000));
+        let error = client
+            .upload_voice_conversion_source(&model, &wav(16_000), RequestOptions::default())
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(error, Error::SampleRateMismatch { .. }),
+            "{error:?}"
+        );
+        assert!(server.requests().is_empty());
+        client
+            .upload_voice_conversion_source(&model, &wav(40_000), RequestOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn download_with_retry_waits_for_storage_to_catch_up() {
+        let server = MockServer::start();
+        server
+            .mock(
+                "GET",
+                "/vocodes-public/audio/late.wav",
+                MockResponse::status(404),
+            )
+            .mock(
+                "GET",
+                "/vocodes-public/audio/late.wav",
+                MockResponse::bytes(b"audio".to_vec()),
+            )
+            .mock(
+                "GET",
+                "/vocodes-public/audio/forbidden.wav",
+                MockResponse::status(403),
+            );
+        let client = server.client();
+        let audio = client
+            .download_audio_with_retry("/audio/late.wav")
+            .await
+            .unwrap();
+        assert_eq!(audio, b"audio");
+        assert_eq!(
+            server
+                .received("GET", "/vocodes-public/audio/late.wav")
+                .len(),
+            2
+        );
+        let error = client
+            .download_audio_with_retry("/audio/forbidden.wav")
+            .await
+            .unwrap_err();
+        assert_eq!(error.status_code(), Some(403));
+        assert_eq!(
+            server
+                .received("GET", "/vocodes-public/audio/forbidden.wav")
+                .len(),
+            1
+        );
+    }
+
+    #[cfg(all(feature = "voices", feature = "language_tags"))]
+    #[tokio::test]
+    async fn voices_match_a_requested_language_canonically() {
+        let server = MockServer::start();
+        let mut british = tts_voice("TM:2");
+        british["ietf_language_tag"] = json!("en-gb");
+        let mut spanish = tts_voice("TM:3");
+        spanish["ietf_language_tag"] = json!("es-ES");
+        server.mock(
+            "GET",
+            "/tts/list",
+            MockResponse::json(json!({
+                "success": true,
+                "models": [tts_voice("TM:1"), british, spanish]
+            })),
+        );
+        let client = server.client();
+        let tokens = |voices: Vec<TtsVoice>| {
+            voices
+                .into_iter()
+                .map(|voice| voice.model_token)
+                .collect::<Vec<_>>()
+        };
+        let english = client
+            .voices_matching_language(&LanguageTag::from("EN"))
+            .await
+            .unwrap();
+        assert_eq!(tokens(english), ["TM:1", "TM:2"]);
+        let american = client
+            .voices_matching_language(&LanguageTag::from("en-us"))
+            .await
+            .unwrap();
+        assert_eq!(tokens(american), ["TM:1"]);
+    }
+
+    #[cfg(all(feature = "voices", feature = "language_tags"))]
+    #[tokio::test]
+    async fn invalid_requested_language_is_rejected_before_listing() {
+        let server = MockServer::start();
+        let error = server
+            .client()
+            .voices_matching_language(&LanguageTag::from("not a tag"))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&error, Error::InvalidLanguageTag { tag } if tag == "not a tag"),
+            "{error:?}"
+        );
+        assert!(server.requests().is_empty());
+        assert!(!LanguageTag::from("en-US").matches(&LanguageTag::from("not a tag")));
+    }
+
+    #[tokio::test]
+    async fn download_results_stream_reports_each_download_in_order() {
+        use futures::StreamExt;
+
+        let server = MockServer::start();
+        let audio = audio_fixture();
+        server
+            .mock(
+                "GET",
+                "/vocodes-public/audio/one.wav",
+                MockResponse::bytes(audio.clone()),
+            )
+            .mock(
+                "GET",
+                "/vocodes-public/audio/two.wav",
+                MockResponse::bytes(b"two".to_vec()),
+            );
+        let result = |token: &str| TtsResult {
+            job_token: token.to_string(),
+            audio_url: format!("/audio/{token}.wav"),
+            maybe_spectrogram_url: None,
+            maybe_duration: None,
+            maybe_inference_text: None,
+        };
+        let results = [result("one"), result("missing"), result("two")];
+        let client = server.client();
+        let events = client
+            .download_results_stream(&results)
+            .collect::<Vec<_>>()
+            .await;
+        // the number of progress events depends on how the body is chunked, so only the last
+        // one of each download is kept
+        let mut sequence = Vec::<String>::new();
+        for event in &events {
+            let step = match event {
+                DownloadEvent::Started { index, maybe_total } => {
+                    format!("started {index} {maybe_total:?}")
+                }
+                DownloadEvent::Progress {
+                    index, downloaded, ..
+                } => format!("progress {index} {downloaded}"),
+                DownloadEvent::Completed { index, audio } => {
+                    format!("completed {index} {}", audio.len())
+                }
+                DownloadEvent::Failed { index, error } => {
+                    format!("failed {index} {:?}", error.status_code())
+                }
+            };
+            match sequence.last_mut() {
+                Some(last) if last.starts_with("progress") && step.starts_with("progress") => {
+                    *last = step
+                }
+                _ => sequence.push(step),
+            }
+        }
+        assert_eq!(
+            sequence,
+            [
+                format!("started 0 Some({})", audio.len()),
+                format!("progress 0 {}", audio.len()),
+                format!("completed 0 {}", audio.len()),
+                "failed 1 Some(404)".to_string(),
+                "started 2 Some(3)".to_string(),
+                "progress 2 3".to_string(),
+                "completed 2 3".to_string(),
+            ]
+        );
+        assert!(matches!(
+            events.last(),
+            Some(DownloadEvent::Completed { audio, .. }) if audio == b"two"
+        ));
+    }
+
+    #[cfg(feature = "face_animator")]
+    #[tokio::test]
+    async fn client_default_dimensions_fill_in_unset_ones() {
+        let server = MockServer::start();
+        server.mock(
+            "POST",
+            "/animation/face_animation/create",
+            MockResponse::json(json!({"success": true, "inference_job_token": "JINF:1"})),
+        );
+        let client = server
+            .client_builder()
+            .default_face_animation_dimensions("twitter_portrait")
+            .build()
+            .unwrap();
+        let payload = |dimensions: Option<&str>| {
+            let mut payload = CreateFaceAnimationPayload::builder();
+            payload
+                .audio_sorce("MU:1".to_string())
+                .image_source("MU:2".to_string());
+            if let Some(dimensions) = dimensions {
+                payload.dimensions(dimensions.to_string());
+            }
+            payload.build().unwrap()
+        };
+        client.create_facial_animation(payload(None)).await.unwrap();
+        client
+            .create_facial_animation(payload(Some("twitter_landscape")))
+            .await
+            .unwrap();
+        let dimensions = server
+            .received("POST", "/animation/face_animation/create")
+            .iter()
+            .map(|request| request.json()["dimensions"].clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            dimensions,
+            [json!("twitter_portrait"), json!("twitter_landscape")]
+        );
+    }
+
+    #[cfg(feature = "face_animator")]
+    #[tokio::test]
+    async fn default_dimensions_are_validated_against_the_server() {
+        let server = MockServer::start();
+        server.mock(
+            "GET",
+            "/animation/face_animation/dimensions",
+            MockResponse::json(json!({
+                "success": true,
+                "dimensions": ["twitter_square", "tiktok_portrait"]
+            })),
+        );
+        server
+            .client()
+            .validate_default_face_animation_dimensions()
+            .await
+            .unwrap();
+        let error = server
+            .client_builder()
+            .default_face_animation_dimensions("twitter_portrait")
+            .build()
+            .unwrap()
+            .validate_default_face_animation_dimensions()
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&error, Error::UnknownFaceAnimationDimensions { dimensions }
+                if dimensions == "twitter_portrait"),
+            "{error:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn retry_retries_a_flaky_operation_with_backoff() {
+        let server = MockServer::start();
+        let (builder, sleeps) = record_sleeps(server.client_builder());
+        let client = builder.build().unwrap();
+        let policy = RetryPolicy::new(3)
+            .backoff(Duration::from_millis(100))
+            .retry_if(|e| matches!(e, Error::TooManyRequestsError));
+        let attempts = std::sync::atomic::AtomicUsize::new(0);
+        let result = client
+            .retry(&policy, || async {
+                match attempts.fetch_add(1, Ordering::SeqCst) {
+                    0 | 1 => Err(Error::TooManyRequestsError),
+                    attempt => Ok(attempt),
+                }
+            })
+            .await
+            .unwrap();
+        assert_eq!(result, 2);
+        assert_eq!(
+            *sleeps.lock().unwrap(),
+            [Duration::from_millis(100), Duration::from_millis(200)]
+        );
+    }
+
+    #[tokio::test]
+    async fn retry_gives_up_on_errors_the_predicate_rejects() {
+        let server = MockServer::start();
+        let client = server.client();
+        let policy = RetryPolicy::new(5).retry_if(|e| matches!(e, Error::TooManyRequestsError));
+        let attempts = std::sync::atomic::AtomicUsize::new(0);
+        let error = client
+            .retry(&policy, || async {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err::<(), _>(Error::NotFound)
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(error, Error::NotFound));
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+        let attempts = std::sync::atomic::AtomicUsize::new(0);
+        let error = client
+            .retry(&policy, || async {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err::<(), _>(Error::TooManyRequestsError)
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(error, Error::TooManyRequestsError));
+        assert_eq!(attempts.load(Ordering::SeqCst), 5);
+    }
+
+    #[cfg(feature = "tts")]
+    #[tokio::test]
+    async fn failed_job_support_info_names_its_worker_and_cluster() {
+        let server = MockServer::start();
+        let mut job = tts_job("JTINF:1", "complete_failure");
+        job["state"]["maybe_assigned_worker"] = json!("worker-7");
+        job["state"]["maybe_assigned_cluster"] = json!("cluster-east");
+        server.mock("GET", "/tts/job/JTINF:1", MockResponse::json(job));
+        let error = server
+            .client()
+            .poll_tts_job_to_result("JTINF:1")
+            .await
+            .unwrap_err();
+        let support_info = error.support_info();
+        assert!(support_info.contains("worker: worker-7"), "{support_info}");
+        assert!(
+            support_info.contains("cluster: cluster-east"),
+            "{support_info}"
+        );
+        assert!(support_info.starts_with(&error.to_string()));
+        assert_eq!(Error::NotFound.support_info(), Error::NotFound.to_string());
+    }
+
+    #[cfg(feature = "tts")]
+    #[tokio::test]
+    async fn warm_model_runs_a_throwaway_inference() {
+        let server = MockServer::start();
+        mock_tts_inference(&server);
+        server.mock(
+            "GET",
+            "/tts/job/JTINF:1",
+            MockResponse::json(tts_job("JTINF:1", "complete_success")),
+        );
+        server.client().warm_model("TM:1").await.unwrap();
+        let submissions = server.received("POST", "/tts/inference");
+        assert_eq!(submissions.len(), 1);
+        assert_eq!(submissions[0].json()["tts_model_token"], "TM:1");
+        assert_eq!(
+            submissions[0].json()["inference_text"],
+            WARMUP_INFERENCE_TEXT
+        );
+        assert_eq!(server.received("GET", "/tts/job/JTINF:1").len(), 1);
+        assert!(server
+            .received("GET", "/vocodes-public/audio/result.wav")
+            .is_empty());
+    }
+
+    #[cfg(any(feature = "tts", feature = "face_animator"))]
+    fn content_token(input: &IdempotencyInput<'_>) -> Uuid {
+        use std::hash::{Hash, Hasher};
+
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        format!("{input:?}").hash(&mut hasher);
+        let hash = hasher.finish();
+        Uuid::from_u64_pair(hash, hash)
+    }
+
+    #[cfg(feature = "tts")]
+    #[tokio::test]
+    async fn deterministic_strategy_gives_identical_inferences_one_token() {
+        let server = MockServer::start();
+        mock_tts_inference(&server);
+        let client = server
+            .client_builder()
+            .idempotency_strategy(IdempotencyStrategy::Deterministic(content_token))
+            .build()
+            .unwrap();
+        for inference_text in ["hello", "hello", "goodbye"] {
+            client.tts_inference("TM:1", inference_text).await.unwrap();
+        }
+        let tokens = server
+            .received("POST", "/tts/inference")
+            .iter()
+            .map(|request| request.json()["uuid_idempotency_token"].clone())
+            .collect::<Vec<_>>();
+        assert_eq!(tokens[0], tokens[1]);
+        assert_ne!(tokens[0], tokens[2]);
+    }
+
+    #[cfg(feature = "face_animator")]
+    #[tokio::test]
+    async fn deterministic_strategy_gives_identical_uploads_one_token() {
+        let server = MockServer::start();
+        server.mock(
+            "POST",
+            "/media_uploads/upload_audio",
+            MockResponse::json(json!({"success": true, "upload_token": "MU:1"})),
+        );
+        let client = server
+            .client_builder()
+            .idempotency_strategy(IdempotencyStrategy::Deterministic(content_token))
+            .build()
+            .unwrap();
+        client.upload_audio(b"audio").await.unwrap();
+        client.upload_audio(b"audio").await.unwrap();
+        let explicit_token = Uuid::new_v4();
+        client
+            .upload_audio_stream(
+                futures::io::Cursor::new(b"audio".to_vec()),
+                5,
+                "audio/wav",
+                RequestOptions::new()
+                    .idempotency_token(explicit_token)
+                    .correlation_id("upload-1"),
+            )
+            .await
+            .unwrap();
+        let requests = server.received("POST", "/media_uploads/upload_audio");
+        let token_of = |request: &crate::mock::ReceivedRequest| {
+            let body = request.body_text();
+            let (_, rest) = body
+                .split_once("name=\"uuid_idempotency_token\"\r\n\r\n")
+                .unwrap();
+            rest.split("\r\n").next().unwrap().to_string()
+        };
+        assert_eq!(token_of(&requests[0]), token_of(&requests[1]));
+        assert_eq!(
+            token_of(&requests[0]),
+            content_token(&IdempotencyInput::Upload {
+                endpoint: "upload_audio",
+                file: b"audio",
+            })
+            .to_string()
+        );
+        assert_eq!(token_of(&requests[2]), explicit_token.to_string());
+        assert_eq!(requests[2].header("x-correlation-id"), Some("upload-1"));
+    }
+
+    #[cfg(feature = "tts")]
+    #[tokio::test]
+    async fn raw_tts_job_keeps_fields_the_typed_job_drops() {
+        let server = MockServer::start();
+        let mut job = tts_job("JTINF:1", "started");
+        job["state"]["maybe_queue_position"] = json!(4);
+        server.mock("GET", "/tts/job/JTINF:1", MockResponse::json(job));
+        let raw = server.client().tts_job_raw("JTINF:1").await.unwrap();
+        assert_eq!(raw["state"]["maybe_queue_position"], 4);
+        assert_eq!(raw["state"]["status"], "started");
+    }
+
+    #[cfg(feature = "face_animator")]
+    #[tokio::test]
+    async fn raw_face_animation_job_keeps_fields_the_typed_job_drops() {
+        let server = MockServer::start();
+        let mut job = face_animation_job("pending");
+        job["state"]["maybe_frontend_failure_category"] = json!("no_face");
+        server.mock(
+            "GET",
+            "/model_inference/job_status/JINF:1",
+            MockResponse::json(job),
+        );
+        let raw = server
+            .client()
+            .face_animation_job_raw("JINF:1")
+            .await
+            .unwrap();
+        assert_eq!(raw["state"]["maybe_frontend_failure_category"], "no_face");
+        assert_eq!(raw["state"]["job_token"], "JINF:1");
+    }
+
+    #[cfg(feature = "tts")]
+    #[tokio::test]
+    async fn correlation_id_is_sent_and_carried_by_errors() {
+        let server = MockServer::start();
+        server.mock("POST", "/tts/inference", MockResponse::status(503));
+        let error = server
+            .client()
+            .tts_inference_with(
+                "TM:1",
+                "hello",
+                TtsInferenceOptions::default(),
+                RequestOptions::new().correlation_id("request-42"),
+            )
+            .await
+            .unwrap_err();
+        let submission = &server.received("POST", "/tts/inference")[0];
+        assert_eq!(submission.header("x-correlation-id"), Some("request-42"));
+        assert_eq!(error.correlation_id(), Some("request-42"));
+        assert!(error.to_string().contains("request-42"), "{error}");
+        assert_eq!(error.status_code(), Some(503));
+        assert!(error.is_transient());
+    }
+
+    #[cfg(feature = "tts")]
+    #[tokio::test(start_paused = true)]
+    async fn cancel_all_polls_stops_every_running_poll() {
+        let server = MockServer::start();
+        let job_tokens = ["JTINF:1", "JTINF:2", "JTINF:3"];
+        for job_token in job_tokens {
+            server.mock(
+                "GET",
+                &format!("/tts/job/{job_token}"),
+                MockResponse::json(tts_job(job_token, "started")),
+            );
+        }
+        let sleeps = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let started_sleeps = sleeps.clone();
+        let client = Client::builder()
+            .base_url(server.uri())
+            .connect_timeout(None)
+            .pool_idle_timeout(None)
+            .redact_error_tokens(false)
+            .sleep_fn(move |duration| {
+                started_sleeps.fetch_add(1, Ordering::SeqCst);
+                tokio::time::sleep(duration)
+            })
+            .build()
+            .unwrap();
+        let polls = job_tokens
+            .into_iter()
+            .map(|job_token| {
+                let client = client.clone();
+                tokio::spawn(async move { client.poll_tts_job(job_token).await })
+            })
+            .collect::<Vec<_>>();
+        while sleeps.load(Ordering::SeqCst) < job_tokens.len() {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        client.cancel_all_polls();
+        let mut cancelled = Vec::new();
+        for poll in polls {
+            match poll.await.unwrap() {
+                Err(Error::PollCancelled { job_token }) => cancelled.push(job_token),
+                result => panic!("poll wasn't cancelled: {result:?}"),
+            }
+        }
+        assert_eq!(cancelled, job_tokens);
+        assert!(client.active_polls.lock().unwrap().handles.is_empty());
+    }
+
+    #[cfg(feature = "face_animator")]
+    #[test]
+    fn entity_types_map_known_and_unknown_values() {
+        for (entity_type, expected) in [
+            ("media_file", EntityType::MediaFile),
+            ("tts_result", EntityType::TtsResult),
+            ("voice_conversion_result", EntityType::VoiceConversionResult),
+            ("image_file", EntityType::Other("image_file".to_string())),
+        ] {
+            assert_eq!(
+                serde_json::from_value::<EntityType>(json!(entity_type)).unwrap(),
+                expected
+            );
+        }
+    }
+
+    #[cfg(feature = "face_animator")]
+    #[tokio::test]
+    async fn face_animation_result_has_a_typed_entity_type() {
+        let server = MockServer::start();
+        let mut job = face_animation_job("complete_success");
+        job["state"]["maybe_result"] = json!({
+            "entity_type": "media_file",
+            "entity_token": "MF:1",
+            "maybe_public_bucket_media_path": "/media/result.mp4"
+        });
+        server.mock(
+            "GET",
+            "/model_inference/job_status/JINF:1",
+            MockResponse::json(job),
+        );
+        let job = server
+            .client()
+            .poll_face_animation_job("JINF:1")
+            .await
+            .unwrap();
+        let result = job.state.maybe_result.unwrap();
+        assert_eq!(result.entity_type, EntityType::MediaFile);
+        assert_eq!(result.entity_token, "MF:1");
+    }
+
+    #[tokio::test]
+    async fn compatibility_check_reports_the_endpoint_that_no_longer_parses() {
+        let server = MockServer::start();
+        server
+            .mock(
+                "GET",
+                "/v1/session",
+                MockResponse::json(json!({"success": true, "logged_in": "yes"})),
+            )
+            .mock(
+                "GET",
+                "/tts/list",
+                MockResponse::json(json!({"success": true, "models": [tts_voice("TM:1")]})),
+            )
+            .mock(
+                "GET",
+                "/v1/voice_conversion/model_list",
+                MockResponse::json(json!({
+                    "success": true,
+                    "models": [{
+                        "token": "VCM:1",
+                        "title": "Voice",
+                        "creator_username": "creator",
+                        "ietf_language_tag": "en-US"
+                    }]
+                })),
+            )
+            .mock(
+                "GET",
+                "/leaderboard",
+                MockResponse::json(json!({"success": true})),
+            );
+        let report = server.client().api_compatibility_check().await.unwrap();
+        assert!(!report.is_compatible());
+        let incompatible = report.incompatible().collect::<Vec<_>>();
+        assert_eq!(incompatible.len(), 1);
+        assert_eq!(incompatible[0].endpoint, "/v1/session");
+        assert!(incompatible[0]
+            .maybe_error
+            .as_deref()
+            .unwrap()
+            .contains("expected a boolean"));
+        assert!(report
+            .endpoints
+            .iter()
+            .filter(|endpoint| endpoint.endpoint != "/v1/session")
+            .all(EndpointCompatibility::is_compatible));
+    }
+
+    #[cfg(feature = "tts")]
+    #[tokio::test]
+    async fn tts_options_are_serialized_only_when_set() {
+        let server = MockServer::start();
+        mock_tts_inference(&server);
+        let client = server.client();
+        for tts_options in [
+            TtsInferenceOptions::new().pitch(-3.0).speed(1.5),
+            TtsInferenceOptions::new().speed(0.5),
+            TtsInferenceOptions::default(),
+        ] {
+            client
+                .tts_inference_with("TM:1", "hello", tts_options, RequestOptions::default())
+                .await
+                .unwrap();
+        }
+        let bodies = server
+            .received("POST", "/tts/inference")
+            .iter()
+            .map(|request| request.json().as_object().unwrap().clone())
+            .collect::<Vec<_>>();
+        assert_eq!(bodies[0]["pitch"], -3.0);
+        assert_eq!(bodies[0]["speed"], 1.5);
+        assert!(!bodies[1].contains_key("pitch"));
+        assert_eq!(bodies[1]["speed"], 0.5);
+        assert!(!bodies[2].contains_key("pitch"));
+        assert!(!bodies[2].contains_key("speed"));
+    }
+
+    #[cfg(feature = "tts")]
+    #[tokio::test]
+    async fn out_of_range_tts_options_are_rejected_before_submitting() {
+        let server = MockServer::start();
+        let error = server
+            .client()
+            .tts_inference_with(
+                "TM:1",
+                "hello",
+                TtsInferenceOptions::new().pitch(13.0),
+                RequestOptions::default(),
+            )
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(
+                error,
+                Error::TtsOptionOutOfRange {
+                    option: "pitch",
+                    ..
+                }
+            ),
+            "{error:?}"
+        );
+        assert!(server.requests().is_empty());
+    }
+
+    #[cfg(all(feature = "tts", feature = "tokio"))]
+    #[tokio::test(start_paused = true)]
+    async fn batch_timings_follow_the_paused_clock() {
+        let server = MockServer::start();
+        server
+            .mock(
+                "POST",
+                "/tts/inference",
+                MockResponse::json(json!({"success": true, "inference_job_token": "JTINF:1"}))
+                    .header("x-ratelimit-remaining", "0")
+                    .header("x-ratelimit-reset", "5"),
+            )
+            .mock(
+                "POST",
+                "/tts/inference",
+                MockResponse::json(json!({"success": true, "inference_job_token": "JTINF:2"})),
+            );
+        for status in ["started", "started", "complete_success"] {
+            server.mock(
+                "GET",
+                "/tts/job/JTINF:1",
+                MockResponse::json(tts_job("JTINF:1", status)),
+            );
+        }
+        for status in ["started", "complete_success"] {
+            server.mock(
+                "GET",
+                "/tts/job/JTINF:2",
+                MockResponse::json(tts_job("JTINF:2", status)),
+            );
+        }
+        // no request timeouts, their timers would let the paused clock jump ahead mid request
+        let client = Client::builder()
+            .base_url(server.uri())
+            .connect_timeout(None)
+            .pool_idle_timeout(None)
+            .tts_poll_config(PollConfig::new(Duration::from_secs(1)))
+            .build()
+            .unwrap();
+        let started = Instant::now();
+        let results = client
+            .synthesize_batch_timed("TM:1", vec!["one".to_string(), "two".to_string()], 1)
+            .await;
+        let timings = results
+            .into_iter()
+            .map(|result| result.unwrap().submit_to_complete)
+            .collect::<Vec<_>>();
+        assert_eq!(timings, [Duration::from_secs(2), Duration::from_secs(1)]);
+        // the second submission waited out the 3s left of the reset, which isn't in its timing
+        assert_eq!(started.elapsed(), Duration::from_secs(6));
+    }
+
+    #[cfg(all(feature = "voices", feature = "tokio"))]
+    #[tokio::test(start_paused = true)]
+    async fn voice_cache_expires_on_the_paused_clock() {
+        let server = MockServer::start();
+        mock_voice_list(&server);
+        let client = Client::builder()
+            .base_url(server.uri())
+            .connect_timeout(None)
+            .pool_idle_timeout(None)
+            .voices_cache_ttl(Some(Duration::from_secs(60)))
+            .build()
+            .unwrap();
+        client.voices().await.unwrap();
+        tokio::time::advance(Duration::from_secs(59)).await;
+        client.voices().await.unwrap();
+        assert_eq!(server.received("GET", "/tts/list").len(), 1);
+        tokio::time::advance(Duration::from_secs(2)).await;
+        assert_eq!(client.voices().await.unwrap().len(), 1);
+        assert_eq!(server.received("GET", "/tts/list").len(), 2);
+    }
+
+    #[cfg(feature = "voices")]
+    #[tokio::test]
+    async fn watch_model_ends_once_the_model_is_removed() {
+        use futures::StreamExt;
+
+        let server = MockServer::start();
+        let mut remoderated = tts_voice("TM:1");
+        remoderated["title"] = json!("Voice (remoderated)");
+        server
+            .mock(
+                "GET",
+                "/tts/model/TM:1",
+                MockResponse::json(json!({"success": true, "model": tts_voice("TM:1")})),
+            )
+            .mock("GET", "/tts/model/TM:1", MockResponse::status(503))
+            .mock(
+                "GET",
+                "/tts/model/TM:1",
+                MockResponse::json(json!({"success": true, "model": remoderated})),
+            )
+            .mock("GET", "/tts/model/TM:1", MockResponse::status(404));
+        let (builder, sleeps) = record_sleeps(server.client_builder());
+        let client = builder.build().unwrap();
+        let updates = client
+            .watch_model("TM:1", Duration::from_secs(30))
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(updates.len(), 3);
+        assert_eq!(updates[0].as_ref().unwrap().title, "Voice");
+        assert_eq!(updates[1].as_ref().unwrap_err().status_code(), Some(503));
+        assert_eq!(updates[2].as_ref().unwrap().title, "Voice (remoderated)");
+        assert_eq!(server.received("GET", "/tts/model/TM:1").len(), 4);
+        assert_eq!(*sleeps.lock().unwrap(), [Duration::from_secs(30); 3]);
+    }
+
+    #[tokio::test]
+    async fn compatibility_check_records_a_failed_request_and_keeps_going() {
+        let server = MockServer::start();
+        server
+            .mock("GET", "/v1/session", MockResponse::status(500))
+            .mock(
+                "GET",
+                "/tts/list",
+                MockResponse::json(json!({"success": true, "models": [tts_voice("TM:1")]})),
+            )
+            .mock(
+                "GET",
+                "/leaderboard",
+                MockResponse::json(json!({"success": true})),
+            );
+        let report = server.client().api_compatibility_check().await.unwrap();
+        assert!(!report.is_compatible());
+        let session = &report.endpoints[0];
+        assert_eq!(session.endpoint, "/v1/session");
+        assert!(session.maybe_request_error.is_some());
+        assert!(session.maybe_error.is_none());
+        #[cfg(feature = "voices")]
+        assert!(report
+            .endpoints
+            .iter()
+            .any(|endpoint| endpoint.endpoint == "/tts/list" && endpoint.is_compatible()));
+    }
 }
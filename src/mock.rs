@@ -0,0 +1,275 @@
+// an in-process http server for tests, answering each route with queued responses. Not
+// every helper is used with every feature combination.
+#![allow(dead_code)]
+
+use std::{
+    collections::VecDeque,
+    convert::Infallible,
+    sync::{Arc, Mutex},
+    thread::JoinHandle,
+    time::Duration,
+};
+
+use futures::channel::oneshot;
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body, Request, Response, Server,
+};
+use reqwest::header::HeaderMap;
+
+use crate::{Client, ClientBuilder};
+
+pub(crate) struct MockServer {
+    uri: String,
+    state: Arc<Mutex<State>>,
+    shutdown: Option<oneshot::Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+#[derive(Default)]
+struct State {
+    routes: Vec<Route>,
+    requests: Vec<ReceivedRequest>,
+    in_flight: usize,
+    max_in_flight: usize,
+    connections: usize,
+}
+
+struct Route {
+    method: String,
+    path: String,
+    responses: VecDeque<MockResponse>,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct MockResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+    delay: Duration,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct ReceivedRequest {
+    pub(crate) method: String,
+    pub(crate) path: String,
+    pub(crate) query: Option<String>,
+    pub(crate) headers: HeaderMap,
+    pub(crate) body: Vec<u8>,
+}
+
+impl MockServer {
+    /// Serves on a random local port from its own thread and runtime, so the tests' runtime
+    /// can pause time without stalling the server.
+    pub(crate) fn start() -> Self {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("failed to bind");
+        listener
+            .set_nonblocking(true)
+            .expect("failed to set nonblocking");
+        let uri = format!("http://{}", listener.local_addr().expect("no local addr"));
+        let state = Arc::new(Mutex::new(State::default()));
+        let (shutdown, shutdown_rx) = oneshot::channel::<()>();
+        let server_state = state.clone();
+        let thread = std::thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("failed to build runtime");
+            runtime.block_on(async move {
+                let make_service = make_service_fn(move |_| {
+                    let state = server_state.clone();
+                    state.lock().unwrap().connections += 1;
+                    async move {
+                        Ok::<_, Infallible>(service_fn(move |request| {
+                            handle(state.clone(), request)
+                        }))
+                    }
+                });
+                let server = Server::from_tcp(listener)
+                    .expect("failed to serve")
+                    .serve(make_service);
+                tokio::select! {
+                    _ = server => {}
+                    _ = shutdown_rx => {}
+                }
+            });
+        });
+        MockServer {
+            uri,
+            state,
+            shutdown: Some(shutdown),
+            thread: Some(thread),
+        }
+    }
+
+    pub(crate) fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    /// The storage base url that `client_builder` resolves media paths against.
+    pub(crate) fn storage_uri(&self) -> String {
+        format!("{}/vocodes-public", self.uri)
+    }
+
+    /// Queues `response` for `method` and `path`, the last queued response of a route is
+    /// repeated once the others have been served. Unmatched requests get an empty 404.
+    pub(crate) fn mock(&self, method: &str, path: &str, response: MockResponse) -> &Self {
+        let mut state = self.state.lock().unwrap();
+        match state
+            .routes
+            .iter_mut()
+            .find(|route| route.method == method && route.path == path)
+        {
+            Some(route) => route.responses.push_back(response),
+            None => state.routes.push(Route {
+                method: method.to_string(),
+                path: path.to_string(),
+                responses: VecDeque::from([response]),
+            }),
+        }
+        self
+    }
+
+    pub(crate) fn requests(&self) -> Vec<ReceivedRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    pub(crate) fn received(&self, method: &str, path: &str) -> Vec<ReceivedRequest> {
+        self.requests()
+            .into_iter()
+            .filter(|request| request.method == method && request.path == path)
+            .collect()
+    }
+
+    /// The most requests that were being handled at the same time.
+    pub(crate) fn max_in_flight(&self) -> usize {
+        self.state.lock().unwrap().max_in_flight
+    }
+
+    /// How many connections clients opened to the server.
+    pub(crate) fn connections(&self) -> usize {
+        self.state.lock().unwrap().connections
+    }
+
+    /// A builder pointed at this server that doesn't sleep between polls and retries.
+    pub(crate) fn client_builder(&self) -> ClientBuilder {
+        Client::builder()
+            .base_url(self.uri())
+            .file_storage_base_url(self.storage_uri())
+            .sleep_fn(|_| async {})
+    }
+
+    pub(crate) fn client(&self) -> Client {
+        self.client_builder().build().unwrap()
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl MockResponse {
+    pub(crate) fn json(body: serde_json::Value) -> Self {
+        MockResponse::bytes(body.to_string().into_bytes())
+            .header("content-type", "application/json")
+    }
+
+    pub(crate) fn bytes<B: Into<Vec<u8>>>(body: B) -> Self {
+        MockResponse {
+            status: 200,
+            headers: Vec::new(),
+            body: body.into(),
+            delay: Duration::ZERO,
+        }
+    }
+
+    pub(crate) fn status(status: u16) -> Self {
+        MockResponse::bytes(Vec::new()).with_status(status)
+    }
+
+    pub(crate) fn with_status(mut self, status: u16) -> Self {
+        self.status = status;
+        self
+    }
+
+    pub(crate) fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Holds the response back for `delay` of real time.
+    pub(crate) fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+}
+
+impl ReceivedRequest {
+    pub(crate) fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).and_then(|value| value.to_str().ok())
+    }
+
+    pub(crate) fn json(&self) -> serde_json::Value {
+        serde_json::from_slice(&self.body).expect("request body isn't json")
+    }
+
+    pub(crate) fn body_text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
+
+async fn handle(
+    state: Arc<Mutex<State>>,
+    request: Request<Body>,
+) -> Result<Response<Body>, Infallible> {
+    let (parts, body) = request.into_parts();
+    {
+        let mut state = state.lock().unwrap();
+        state.in_flight += 1;
+        state.max_in_flight = state.max_in_flight.max(state.in_flight);
+    }
+    let body = hyper::body::to_bytes(body)
+        .await
+        .map(|body| body.to_vec())
+        .unwrap_or_default();
+    let method = parts.method.to_string();
+    let path = parts.uri.path().to_string();
+    let response = {
+        let mut state = state.lock().unwrap();
+        state.requests.push(ReceivedRequest {
+            method: method.clone(),
+            path: path.clone(),
+            query: parts.uri.query().map(str::to_string),
+            headers: parts.headers,
+            body,
+        });
+        state
+            .routes
+            .iter_mut()
+            .find(|route| route.method == method && route.path == path)
+            .and_then(|route| match route.responses.len() {
+                0 => None,
+                1 => route.responses.front().cloned(),
+                _ => route.responses.pop_front(),
+            })
+            .unwrap_or_else(|| MockResponse::status(404))
+    };
+    if !response.delay.is_zero() {
+        tokio::time::sleep(response.delay).await;
+    }
+    state.lock().unwrap().in_flight -= 1;
+    let mut builder = Response::builder().status(response.status);
+    for (name, value) in &response.headers {
+        builder = builder.header(name, value);
+    }
+    Ok(builder
+        .body(Body::from(response.body))
+        .expect("invalid mock response"))
+}
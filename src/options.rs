@@ -0,0 +1,194 @@
+use std::{ops::RangeInclusive, time::Duration};
+
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue},
+    RequestBuilder,
+};
+use uuid::Uuid;
+
+use crate::Error;
+
+const CORRELATION_ID_HEADER: &str = "x-correlation-id";
+const PITCH_RANGE: RangeInclusive<f32> = -12.0..=12.0;
+const SPEED_RANGE: RangeInclusive<f32> = 0.5..=2.0;
+
+#[derive(Debug, Clone, Default)]
+pub struct RequestOptions {
+    pub(crate) idempotency_token: Option<Uuid>,
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) headers: HeaderMap,
+    pub(crate) correlation_id: Option<String>,
+}
+
+impl RequestOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn idempotency_token(mut self, idempotency_token: Uuid) -> Self {
+        self.idempotency_token = Some(idempotency_token);
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sends `correlation_id` as `X-Correlation-Id` and attaches it to tracing spans and to
+    /// errors, as `Error::Correlated`, to tie requests to the caller's own request context.
+    pub fn correlation_id<S: Into<String>>(mut self, correlation_id: S) -> Self {
+        self.correlation_id = Some(correlation_id.into());
+        self
+    }
+
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+
+    pub fn headers(mut self, headers: HeaderMap) -> Self {
+        self.headers.extend(headers);
+        self
+    }
+
+    // an explicit token always wins over the client's strategy
+    #[cfg_attr(
+        not(any(
+            feature = "tts",
+            feature = "face_animator",
+            feature = "voice_conversion"
+        )),
+        allow(dead_code)
+    )]
+    pub(crate) fn idempotency_token_or(
+        &self,
+        strategy: IdempotencyStrategy,
+        input: IdempotencyInput<'_>,
+    ) -> Uuid {
+        self.idempotency_token
+            .unwrap_or_else(|| strategy.idempotency_token(&input))
+    }
+
+    #[cfg_attr(
+        not(any(
+            feature = "tts",
+            feature = "face_animator",
+            feature = "voice_conversion"
+        )),
+        allow(dead_code)
+    )]
+    pub(crate) fn apply(&self, mut request: RequestBuilder) -> RequestBuilder {
+        if let Some(timeout) = self.timeout {
+            request = request.timeout(timeout);
+        }
+        request = request.headers(self.headers.clone());
+        if let Some(correlation_id) = &self.correlation_id {
+            request = request.header(CORRELATION_ID_HEADER, correlation_id);
+        }
+        request
+    }
+
+    #[cfg_attr(
+        not(any(
+            feature = "tts",
+            feature = "face_animator",
+            feature = "voice_conversion"
+        )),
+        allow(dead_code)
+    )]
+    pub(crate) fn correlate<T>(&self, result: Result<T, Error>) -> Result<T, Error> {
+        match &self.correlation_id {
+            Some(correlation_id) => result.map_err(|e| Error::Correlated {
+                correlation_id: correlation_id.clone(),
+                source: Box::new(e),
+            }),
+            None => result,
+        }
+    }
+}
+
+/// Prosody controls for a tts inference. Only some model types accept them, the api ignores
+/// them for the rest. Unset options aren't sent.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TtsInferenceOptions {
+    pub(crate) pitch: Option<f32>,
+    pub(crate) speed: Option<f32>,
+}
+
+impl TtsInferenceOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pitch shift in semitones, from -12 to 12.
+    pub fn pitch(mut self, pitch: f32) -> Self {
+        self.pitch = Some(pitch);
+        self
+    }
+
+    /// Speaking rate multiplier, from 0.5 to 2.
+    pub fn speed(mut self, speed: f32) -> Self {
+        self.speed = Some(speed);
+        self
+    }
+
+    #[cfg_attr(not(feature = "tts"), allow(dead_code))]
+    pub(crate) fn validate(&self) -> Result<(), Error> {
+        for (option, maybe_value, range) in [
+            ("pitch", self.pitch, PITCH_RANGE),
+            ("speed", self.speed, SPEED_RANGE),
+        ] {
+            match maybe_value {
+                Some(value) if !range.contains(&value) => {
+                    return Err(Error::TtsOptionOutOfRange {
+                        option,
+                        value,
+                        min: *range.start(),
+                        max: *range.end(),
+                    });
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The request a deterministic [`IdempotencyStrategy`] derives its token from.
+#[derive(Debug, Clone, Copy)]
+pub enum IdempotencyInput<'a> {
+    TtsInference {
+        tts_model_token: &'a str,
+        inference_text: &'a str,
+        tts_options: &'a TtsInferenceOptions,
+    },
+    Upload {
+        endpoint: &'a str,
+        file: &'a [u8],
+    },
+    VoiceConversion {
+        voice_conversion_model_token: &'a str,
+        source_media_upload_token: &'a str,
+    },
+}
+
+/// How idempotency tokens are generated for requests without an explicit
+/// [`RequestOptions::idempotency_token`]. `Deterministic` lets identical requests share a
+/// token, so a resubmission is deduplicated by the server. Streamed uploads can't be hashed
+/// and always get a random token.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum IdempotencyStrategy {
+    #[default]
+    Random,
+    Deterministic(fn(&IdempotencyInput<'_>) -> Uuid),
+}
+
+impl IdempotencyStrategy {
+    pub fn idempotency_token(&self, input: &IdempotencyInput<'_>) -> Uuid {
+        match self {
+            IdempotencyStrategy::Random => Uuid::new_v4(),
+            IdempotencyStrategy::Deterministic(derive) => derive(input),
+        }
+    }
+}
@@ -0,0 +1,214 @@
+use std::{fmt, future::Future, pin::Pin, sync::Arc, time::Duration};
+
+#[cfg(any(
+    feature = "tts",
+    feature = "face_animator",
+    feature = "voice_conversion"
+))]
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+type SleepFn = dyn Fn(Duration) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync;
+
+/// The async sleep used between polls, so polling isn't tied to a specific runtime.
+#[derive(Clone)]
+pub(crate) struct Sleeper {
+    sleep_fn: Arc<SleepFn>,
+    // a blocking sleep holds up the thread, so it can't be raced against other futures
+    blocking: bool,
+}
+
+impl Sleeper {
+    pub(crate) fn new<F, Fut>(sleep_fn: F) -> Self
+    where
+        F: Fn(Duration) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        Sleeper {
+            sleep_fn: Arc::new(move |duration| Box::pin(sleep_fn(duration))),
+            blocking: false,
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    pub(crate) fn tokio() -> Self {
+        Sleeper::new(tokio::time::sleep)
+    }
+
+    #[cfg(feature = "blocking")]
+    pub(crate) fn blocking() -> Self {
+        Sleeper {
+            blocking: true,
+            ..Sleeper::new(|duration| async move { std::thread::sleep(duration) })
+        }
+    }
+
+    #[cfg_attr(any(feature = "tokio", not(feature = "tts")), allow(dead_code))]
+    pub(crate) fn is_blocking(&self) -> bool {
+        self.blocking
+    }
+
+    pub(crate) fn sleep(&self, duration: Duration) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        (self.sleep_fn)(duration)
+    }
+}
+
+impl fmt::Debug for Sleeper {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Sleeper")
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PollConfig {
+    #[cfg_attr(not(any(feature = "tts", feature = "face_animator")), allow(dead_code))]
+    pub(crate) interval: Duration,
+    pub(crate) jitter: f64,
+    pub(crate) seed: Option<u64>,
+    pub(crate) max_polls: Option<usize>,
+    pub(crate) backoff_factor: f64,
+    pub(crate) max_interval: Option<Duration>,
+}
+
+impl PollConfig {
+    pub fn new(interval: Duration) -> Self {
+        PollConfig {
+            interval,
+            jitter: 0.0,
+            seed: None,
+            max_polls: None,
+            backoff_factor: 1.0,
+            max_interval: None,
+        }
+    }
+
+    /// Randomizes every interval by up to ±`jitter` (a fraction between 0 and 1) so that
+    /// many clients polling at once spread out their requests.
+    pub fn jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Seeds the jitter rng, mostly useful for deterministic tests.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Gives up on a job after `max_polls` non-terminal responses with
+    /// `Error::PollLimitExceeded`, regardless of how long that took. Unlimited by default.
+    pub fn max_polls(mut self, max_polls: usize) -> Self {
+        self.max_polls = Some(max_polls);
+        self
+    }
+
+    /// Multiplies the interval by `factor` after every poll, up to `max_interval`. Useful for
+    /// long running jobs such as face animations, which then need far fewer requests.
+    pub fn backoff(mut self, factor: f64, max_interval: Duration) -> Self {
+        self.backoff_factor = factor.max(1.0);
+        self.max_interval = Some(max_interval);
+        self
+    }
+}
+
+impl Default for PollConfig {
+    fn default() -> Self {
+        PollConfig::new(Duration::from_secs(8))
+    }
+}
+
+#[cfg(any(
+    feature = "tts",
+    feature = "face_animator",
+    feature = "voice_conversion"
+))]
+#[derive(Debug)]
+pub(crate) struct PollSchedule {
+    config: PollConfig,
+    rng: StdRng,
+    polls: usize,
+    interval: Duration,
+}
+
+#[cfg(any(
+    feature = "tts",
+    feature = "face_animator",
+    feature = "voice_conversion"
+))]
+impl PollSchedule {
+    pub(crate) fn new(config: &PollConfig) -> Self {
+        let rng = match config.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        PollSchedule {
+            config: config.clone(),
+            rng,
+            polls: 0,
+            interval: config.interval,
+        }
+    }
+
+    // counts a non-terminal poll, returning the count once the limit is reached
+    pub(crate) fn record_poll(&mut self) -> Option<usize> {
+        self.polls += 1;
+        self.config
+            .max_polls
+            .filter(|max_polls| self.polls >= *max_polls)
+            .map(|_| self.polls)
+    }
+
+    pub(crate) fn next_interval(&mut self) -> Duration {
+        let interval = self.interval;
+        if self.config.backoff_factor > 1.0 {
+            let next = interval.mul_f64(self.config.backoff_factor);
+            self.interval = match self.config.max_interval {
+                Some(max_interval) => next.min(max_interval),
+                None => next,
+            };
+        }
+        if self.config.jitter == 0.0 {
+            return interval;
+        }
+        let factor = 1.0 + self.rng.gen_range(-self.config.jitter..=self.config.jitter);
+        interval.mul_f64(factor)
+    }
+}
+
+#[cfg(all(test, feature = "tts"))]
+mod tests {
+    use super::*;
+
+    fn intervals(config: &PollConfig, count: usize) -> Vec<Duration> {
+        let mut schedule = PollSchedule::new(config);
+        (0..count).map(|_| schedule.next_interval()).collect()
+    }
+
+    #[test]
+    fn seeded_jitter_stays_within_range() {
+        let config = PollConfig::new(Duration::from_secs(8)).jitter(0.25).seed(7);
+        let jittered = intervals(&config, 100);
+        for interval in &jittered {
+            assert!(
+                (Duration::from_secs(6)..=Duration::from_secs(10)).contains(interval),
+                "{interval:?}"
+            );
+        }
+        assert!(jittered.iter().any(|interval| *interval != jittered[0]));
+        assert_eq!(jittered, intervals(&config, 100));
+    }
+
+    #[test]
+    fn no_jitter_keeps_the_interval() {
+        let config = PollConfig::new(Duration::from_secs(8));
+        assert_eq!(intervals(&config, 3), [Duration::from_secs(8); 3]);
+    }
+
+    #[test]
+    fn backoff_grows_the_interval_up_to_the_cap() {
+        let config = PollConfig::new(Duration::from_secs(10)).backoff(2.0, Duration::from_secs(40));
+        assert_eq!(
+            intervals(&config, 5),
+            [10, 20, 40, 40, 40].map(Duration::from_secs)
+        );
+    }
+}
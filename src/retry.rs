@@ -0,0 +1,63 @@
+use std::{fmt, sync::Arc, time::Duration};
+
+use crate::Error;
+
+type RetryPredicate = dyn Fn(&Error) -> bool + Send + Sync;
+
+/// Decides how often and when `Client::retry` retries a failed operation.
+#[derive(Clone)]
+pub struct RetryPolicy {
+    pub(crate) max_attempts: usize,
+    pub(crate) backoff: Duration,
+    pub(crate) backoff_factor: f64,
+    pub(crate) retryable: Arc<RetryPredicate>,
+}
+
+impl RetryPolicy {
+    /// Tries an operation up to `max_attempts` times, including the first attempt.
+    pub fn new(max_attempts: usize) -> Self {
+        RetryPolicy {
+            max_attempts: max_attempts.max(1),
+            backoff: Duration::from_millis(500),
+            backoff_factor: 2.0,
+            retryable: Arc::new(Error::is_transient),
+        }
+    }
+
+    /// Sets the wait before the first retry, defaults to 500ms.
+    pub fn backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Multiplies the wait after every retry, defaults to 2.
+    pub fn backoff_factor(mut self, backoff_factor: f64) -> Self {
+        self.backoff_factor = backoff_factor.max(1.0);
+        self
+    }
+
+    /// Only retries errors matching `retryable`. Defaults to server errors and timeouts.
+    pub fn retry_if<F>(mut self, retryable: F) -> Self
+    where
+        F: Fn(&Error) -> bool + Send + Sync + 'static,
+    {
+        self.retryable = Arc::new(retryable);
+        self
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy::new(3)
+    }
+}
+
+impl fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("backoff", &self.backoff)
+            .field("backoff_factor", &self.backoff_factor)
+            .finish_non_exhaustive()
+    }
+}